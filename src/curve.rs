@@ -0,0 +1,303 @@
+//! Evaluates the free-form curves of `.obj` format
+
+use crate::freeform::{Basis, Curve, Curve2D, FreeFormAttributes};
+use crate::nurbs;
+use crate::obj::Obj;
+
+/// Upper limit of the depth of recursive subdivision in `CurveEvaluator::flatten`.
+const MAX_DEPTH: u32 = 16;
+
+/// Evaluates a `Curve` or a `Curve2D` of any basis at a parameter value.
+///
+/// Points of a `Curve2D` are returned in the parameter space of a surface, as `[u, v, 0.0]`.
+#[derive(Clone, Debug)]
+pub struct CurveEvaluator {
+    form: Form,
+    /// Control points in homogeneous coordinates, i.e. `[x * w, y * w, z * w, w]`.
+    points: Vec<[f32; 4]>,
+    rational: bool,
+    domain: [f32; 2],
+}
+
+/// Representation of a curve which is suitable for its basis.
+#[derive(Clone, Debug)]
+enum Form {
+    /// A B-spline, which is evaluated with the knot vector.
+    BSpline {
+        knots: Vec<f32>,
+        degree: usize,
+    },
+    /// A sequence of polynomial segments, each of which is `sum_i P_i sum_j B_ij t^j` for the
+    /// `degree + 1` control points starting from `step * segment`.
+    Matrix {
+        matrix: Vec<f32>,
+        degree: usize,
+        step: usize,
+        /// Global parameter values at the ends of the segments.
+        parameters: Vec<f32>,
+    },
+}
+
+impl CurveEvaluator {
+    /// Prepares `curve` of `obj` for evaluation. The curve is evaluated within its range.
+    ///
+    /// Returns `None` if the curve references missing vertices, or its attributes do not match
+    /// its control points, e.g. the knot vector of a B-spline has a wrong length.
+    pub fn new(obj: &Obj, curve: &Curve) -> Option<Self> {
+        let rational = curve.attributes.rational;
+
+        let mut points = Vec::with_capacity(curve.control_points.len());
+        for &index in curve.control_points.iter() {
+            let vertex = obj.vertices.get(obj.position(index as usize)?)?;
+            let w = if rational { vertex[3] } else { 1.0 };
+            points.push([vertex[0] * w, vertex[1] * w, vertex[2] * w, w]);
+        }
+
+        let mut evaluator = Self::with_points(&curve.attributes, &curve.parameters, points)?;
+        let [start, end] = evaluator.domain;
+        if curve.range[0] < curve.range[1] {
+            evaluator.domain = [curve.range[0].max(start), curve.range[1].min(end)];
+        }
+        if evaluator.domain[0] >= evaluator.domain[1] { return None }
+
+        Some(evaluator)
+    }
+
+    /// Prepares `curve` of `obj` for evaluation. The curve is evaluated within its whole domain.
+    ///
+    /// Returns `None` under the same conditions as `CurveEvaluator::new`.
+    pub fn new_2d(obj: &Obj, curve: &Curve2D) -> Option<Self> {
+        let rational = curve.attributes.rational;
+
+        let mut points = Vec::with_capacity(curve.control_points.len());
+        for &index in curve.control_points.iter() {
+            let vertex = obj.param_vertices.get(obj.position(index as usize)?)?;
+            let w = if rational { vertex[2] } else { 1.0 };
+            points.push([vertex[0] * w, vertex[1] * w, 0.0, w]);
+        }
+
+        Self::with_points(&curve.attributes, &curve.parameters, points)
+    }
+
+    /// Constructs an evaluator of the curve whose control points are `points`, and whose global
+    /// parameter values given by `parm u` are `parameters`.
+    fn with_points(attributes: &FreeFormAttributes, parameters: &[f32], points: Vec<[f32; 4]>)
+        -> Option<Self>
+    {
+        let degree = attributes.degree[0] as usize;
+        let count = points.len();
+
+        // Every basis needs at least `degree + 1` control points. The check comes before
+        // building the matrices, whose size grows with the square of the degree.
+        let size = degree.checked_add(1)?;
+        if count < size { return None }
+
+        let (matrix, step) = match attributes.basis? {
+            Basis::BSpline => {
+                if !nurbs::is_valid(parameters, degree, count) { return None }
+
+                let domain = nurbs::domain(parameters, degree, count);
+                let form = Form::BSpline { knots: parameters.to_vec(), degree };
+                return Some(CurveEvaluator { form, points, rational: attributes.rational, domain })
+            }
+            Basis::Bezier => (bezier_matrix(degree), degree),
+            Basis::Cardinal if degree == 3 => (cardinal_matrix(), 1),
+            Basis::Cardinal => return None,
+            Basis::Taylor => (taylor_matrix(degree), degree + 1),
+            Basis::BMatrix => {
+                (attributes.basis_matrix[0].clone(), attributes.step[0] as usize)
+            }
+        };
+
+        if step == 0 || Some(matrix.len()) != size.checked_mul(size) { return None }
+        if !(count - size).is_multiple_of(step) { return None }
+        let segments = (count - size) / step + 1;
+
+        let parameters = match parameters.len() {
+            0 => (0..segments + 1).map(|i| i as f32).collect(),
+            len if len == segments + 1 => parameters.to_vec(),
+            _ => return None
+        };
+        if parameters.windows(2).any(|pair| pair[0] >= pair[1]) { return None }
+
+        let domain = [parameters[0], parameters[segments]];
+        let form = Form::Matrix { matrix, degree, step, parameters };
+        Some(CurveEvaluator { form, points, rational: attributes.rational, domain })
+    }
+
+    /// Returns the range of the parameter values at which the curve is evaluated.
+    pub fn domain(&self) -> [f32; 2] {
+        self.domain
+    }
+
+    /// Evaluates the point at `t`, which is clamped into the domain.
+    pub fn evaluate(&self, t: f32) -> [f32; 3] {
+        nurbs::project(self.homogeneous(t, false))
+    }
+
+    /// Evaluates the first derivative with respect to `t`, which is clamped into the domain.
+    pub fn derivative(&self, t: f32) -> [f32; 3] {
+        let point = self.homogeneous(t, false);
+        let derivative = self.homogeneous(t, true);
+
+        // Quotient rule, since the point is the homogeneous point divided by its weight
+        let w = if point[3] == 0.0 { 1.0 } else { point[3] };
+        let c = nurbs::project(point);
+        [
+            (derivative[0] - derivative[3] * c[0]) / w,
+            (derivative[1] - derivative[3] * c[1]) / w,
+            (derivative[2] - derivative[3] * c[2]) / w,
+        ]
+    }
+
+    /// Approximates the curve with a polyline, whose segments deviate from the curve by at most
+    /// `tolerance`. The polyline starts and ends at the ends of the domain.
+    pub fn flatten(&self, tolerance: f32) -> Vec<[f32; 3]> {
+        let breakpoints = self.breakpoints(self.domain);
+
+        let mut points = vec![self.evaluate(breakpoints[0])];
+        for span in breakpoints.windows(2) {
+            self.refine(span[0], span[1], tolerance, 0, &mut points);
+        }
+        points
+    }
+
+    /// Returns the ends of `range` and the knots or the ends of the segments between them, in the
+    /// order from `range[0]` to `range[1]`. The curve is a polynomial between each of them.
+    pub(crate) fn breakpoints(&self, range: [f32; 2]) -> Vec<f32> {
+        let parameters = match self.form {
+            Form::BSpline { ref knots, .. } => knots,
+            Form::Matrix { ref parameters, .. } => parameters,
+        };
+
+        let [start, end] = range;
+        let mut breakpoints = nurbs::breakpoints(parameters, [start.min(end), start.max(end)]);
+        if start > end { breakpoints.reverse() }
+        breakpoints
+    }
+
+    /// Appends the points which approximate the curve from `start` to `end` to `points`, whose
+    /// last element is the point at `start`.
+    fn refine(&self, start: f32, end: f32, tolerance: f32, depth: u32, points: &mut Vec<[f32; 3]>) {
+        let a = *points.last().unwrap();
+        let b = self.evaluate(end);
+
+        // Testing a single midpoint misses S-shaped spans, whose midpoint may lie on the chord
+        let flat = (1..4).all(|k| {
+            let t = start + (end - start) * k as f32 / 4.0;
+            nurbs::deviation(a, b, self.evaluate(t)) <= tolerance
+        });
+        if flat || depth == MAX_DEPTH {
+            points.push(b);
+            return
+        }
+
+        let middle = (start + end) / 2.0;
+        self.refine(start, middle, tolerance, depth + 1, points);
+        self.refine(middle, end, tolerance, depth + 1, points);
+    }
+
+    /// Evaluates the homogeneous point, or its derivative if `derivative` is set, at `t`.
+    fn homogeneous(&self, t: f32, derivative: bool) -> [f32; 4] {
+        let t = t.clamp(self.domain[0], self.domain[1]);
+
+        let mut result = match self.form {
+            Form::BSpline { ref knots, degree } if derivative => {
+                if degree == 0 { return [0.0; 4] }
+
+                let (knots, points) = nurbs::derivative(knots, degree, &self.points);
+                nurbs::curve_point(&knots, degree - 1, &points, t)
+            }
+            Form::BSpline { ref knots, degree } => {
+                nurbs::curve_point(knots, degree, &self.points, t)
+            }
+            Form::Matrix { ref matrix, degree, step, ref parameters } => {
+                let segments = parameters.len() - 1;
+                let segment = parameters[1..segments].iter()
+                    .take_while(|&&end| t >= end)
+                    .count();
+                let (start, end) = (parameters[segment], parameters[segment + 1]);
+                let local = (t - start) / (end - start);
+
+                // Coefficients of each control point, which are `B_ij` multiplied by `t^j` or
+                // its derivative with respect to the global parameter
+                let size = degree + 1;
+                let mut powers = vec![0.0; size];
+                for (j, power) in powers.iter_mut().enumerate() {
+                    *power = match (derivative, j) {
+                        (false, _) => local.powi(j as i32),
+                        (true, 0) => 0.0,
+                        (true, _) => j as f32 * local.powi(j as i32 - 1) / (end - start),
+                    };
+                }
+
+                let mut result = [0.0; 4];
+                for i in 0..size {
+                    let point = self.points[segment * step + i];
+                    let row = &matrix[i * size..(i + 1) * size];
+                    let coefficient: f32 = row.iter().zip(powers.iter()).map(|(b, p)| b * p).sum();
+                    for (value, component) in result.iter_mut().zip(point.iter()) {
+                        *value += coefficient * component;
+                    }
+                }
+                result
+            }
+        };
+
+        // Bases such as Taylor do not sum to one, so the weights of a non-rational curve are not
+        // blended
+        if !self.rational {
+            result[3] = if derivative { 0.0 } else { 1.0 };
+        }
+        result
+    }
+}
+
+/// Returns the basis matrix of a Bezier segment of `degree`, i.e. the coefficients of the
+/// Bernstein polynomials.
+fn bezier_matrix(degree: usize) -> Vec<f32> {
+    let binomial = |n: usize, k: usize| (0..k).fold(1.0, |product, i| {
+        product * (n - i) as f32 / (i + 1) as f32
+    });
+
+    let size = degree + 1;
+    let mut matrix = vec![0.0; size * size];
+    for i in 0..size {
+        for j in i..size {
+            let sign = if (j - i) % 2 == 0 { 1.0 } else { -1.0 };
+            matrix[i * size + j] = sign * binomial(degree, j) * binomial(j, i);
+        }
+    }
+    matrix
+}
+
+/// Returns the basis matrix of a cubic Cardinal segment, i.e. a Catmull-Rom spline.
+fn cardinal_matrix() -> Vec<f32> {
+    vec![
+        0.0, -0.5,  1.0, -0.5,
+        1.0,  0.0, -2.5,  1.5,
+        0.0,  0.5,  2.0, -1.5,
+        0.0,  0.0, -0.5,  0.5,
+    ]
+}
+
+/// Returns the basis matrix of a Taylor segment of `degree`, whose control points are the
+/// coefficients of the polynomial.
+fn taylor_matrix(degree: usize) -> Vec<f32> {
+    let size = degree + 1;
+    let mut matrix = vec![0.0; size * size];
+    for i in 0..size {
+        matrix[i * size + i] = 1.0;
+    }
+    matrix
+}
+
+#[test]
+fn test_bezier_matrix() {
+    assert_eq!(bezier_matrix(3), vec![
+        1.0, -3.0,  3.0, -1.0,
+        0.0,  3.0, -6.0,  3.0,
+        0.0,  0.0,  3.0, -3.0,
+        0.0,  0.0,  0.0,  1.0,
+    ]);
+}
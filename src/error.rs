@@ -1,31 +1,173 @@
 //! Contains helper structs for error handling
 
-#[derive(Copy)]
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// A type for results generated by `load_obj` and `load_mtl` which returns `ObjError` on failure.
+pub type ObjResult<T> = Result<T, ObjError>;
+
+/// The error type for loading of the `obj` file.
+#[derive(Debug)]
+pub enum ObjError {
+    /// IO error has been occurred during opening the `obj` file.
+    Io(io::Error),
+    /// Tried to parse a malformed `obj` file.
+    Parse(ParseError),
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ObjError::Io(ref e) => fmt::Display::fmt(e, f),
+            ObjError::Parse(ref e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ObjError::Io(ref e) => Some(e),
+            ObjError::Parse(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+impl From<ParseError> for ObjError {
+    fn from(err: ParseError) -> Self {
+        ObjError::Parse(err)
+    }
+}
+
+
+/// An error which can be returned when parsing a wavefront `obj` or `mtl` statement.
+///
+/// `ParseError`s returned from `load_obj` and `load_mtl` are located, i.e. they know which line
+/// and which token of the input caused the failure. Its `Display` implementation prints a
+/// compiler-style diagnostic such as:
+///
+/// ```text
+/// error: Wrong type of arguments
+///  --> 3:7
+///   |
+/// 3 | v 1.0 abc 2.0
+///   |       ^^^
+/// ```
+#[derive(Clone, Debug)]
 pub struct ParseError {
+    /// Kind of the error.
     pub kind: ParseErrorKind,
+    /// Short description of the error.
     pub desc: &'static str,
+    /// Line number of the offending statement, starting from 1. `0` if unknown.
+    pub line: usize,
+    /// Column of the offending token in characters, starting from 1. `0` if unknown.
+    pub column: usize,
+    /// Byte offset of the offending token from the beginning of the input.
+    pub offset: usize,
+    /// Keyword of the offending statement, such as `"f"` or `"newmtl"`.
+    pub stmt: String,
+    /// The offending token, if the error was caused by a specific argument.
+    pub token: Option<String>,
+    /// Raw text of the offending line, without the trailing newline.
+    pub text: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error: {}", self.desc)?;
+        if self.line == 0 {
+            return match self.token {
+                Some(ref token) => write!(f, " `{}`", token),
+                None => Ok(())
+            }
+        }
+
+        let number = self.line.to_string();
+        let margin: String = number.chars().map(|_| ' ').collect();
+        write!(f, "\n{}--> {}:{}\n{} |\n{} | {}\n{} | ", margin, self.line, self.column,
+               margin, number, self.text, margin)?;
+
+        // Keep tabs in the indentation so that the marker lines up with the token
+        for c in self.text.chars().take(self.column.saturating_sub(1)) {
+            f.write_str(if c == '\t' { "\t" } else { " " })?;
+        }
+        let width = match self.token {
+            Some(ref token) => token.chars().count(),
+            None => self.stmt.chars().count(),
+        };
+        for _ in 0..width.max(1) {
+            f.write_str("^")?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Copy)]
+impl Error for ParseError {}
+
+/// Enum to store the various types of errors that can cause parsing an `obj` file to fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ParseErrorKind {
+    /// Met unexpected statement.
     UnexpectedStatement,
+    /// Met a statement which is valid but not supported yet.
+    UnsupportedStatement,
+    /// Received wrong number of arguments.
     WrongNumberOfArguments,
+    /// Received an argument which could not be parsed, such as an invalid number.
+    WrongTypeOfArguments,
+    /// Vertices of a single element were written in different formats.
+    InconsistentVertexFormat,
+    /// A material library referenced by `mtllib` could not be opened.
+    MissingMaterialLibrary,
+    /// A material referenced by `usemtl` is not defined in any material library.
+    UndefinedMaterial,
+    /// A file referenced by `call` could not be opened.
+    MissingCallTarget,
+    /// A file includes itself by `call`, directly or indirectly.
+    RecursiveCall,
 }
 
+/// Constructs a `ParseError` of the given kind. Its location is filled in later by the lexer.
 pub fn parse_error(kind: ParseErrorKind) -> ParseError {
     let desc = match kind {
         ParseErrorKind::UnexpectedStatement => "Unexpected statement",
+        ParseErrorKind::UnsupportedStatement => "Unsupported statement",
         ParseErrorKind::WrongNumberOfArguments => "Wrong number of arguments",
+        ParseErrorKind::WrongTypeOfArguments => "Wrong type of arguments",
+        ParseErrorKind::InconsistentVertexFormat => "Inconsistent vertex format",
+        ParseErrorKind::MissingMaterialLibrary => "Missing material library",
+        ParseErrorKind::UndefinedMaterial => "Undefined material",
+        ParseErrorKind::MissingCallTarget => "Missing call target",
+        ParseErrorKind::RecursiveCall => "Recursive call",
     };
 
     ParseError {
-        kind: kind,
-        desc: desc,
+        kind,
+        desc,
+        line: 0,
+        column: 0,
+        offset: 0,
+        stmt: String::new(),
+        token: None,
+        text: String::new(),
     }
 }
 
 macro_rules! error {
     ($kind:ident) => {
-        return Some(parse_error(ParseErrorKind::$kind))
-    }
+        return Err($crate::error::parse_error($crate::error::ParseErrorKind::$kind))
+    };
+    ($kind:ident, $token:expr) => ({
+        let mut e = $crate::error::parse_error($crate::error::ParseErrorKind::$kind);
+        e.token = Some($token.to_string());
+        return Err(e)
+    })
 }
@@ -0,0 +1,458 @@
+//! Contains the free-form geometry of `.obj` format, such as curves and surfaces
+
+use std::slice;
+use crate::lex::{n, index};
+use crate::error::ParseError;
+use crate::obj::{corner, polygon, MixedFormats, Polygon};
+
+/// Free-form attributes, which are set by `cstype`, `deg`, `bmat` and `step` statements, and the
+/// approximation techniques set by `ctech` and `stech` statements.
+///
+/// The attributes are stateful, i.e. each free-form element takes the attributes which are in
+/// effect when it is declared. Each pair of values is given in the order of `u` and `v`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FreeFormAttributes {
+    /// Whether the curve or surface is rational, i.e. its control points have weights.
+    pub rational: bool,
+    /// Type of the basis, or `None` if there has been no `cstype` statement.
+    pub basis: Option<Basis>,
+    /// Degrees of the basis. `0` if not given.
+    pub degree: [u32; 2],
+    /// Basis matrices of `Basis::BMatrix`, in row-major order. Each of them has `(degree + 1)^2`
+    /// elements, or none if not given.
+    pub basis_matrix: [Vec<f32>; 2],
+    /// Step sizes of `Basis::BMatrix`, `Basis::Cardinal` and `Basis::Taylor`. `0` if not given.
+    pub step: [u32; 2],
+    /// Technique to approximate curves with lines, or `None` if there has been no `ctech`.
+    pub curve_technique: Option<CurveTechnique>,
+    /// Technique to approximate surfaces with polygons, or `None` if there has been no `stech`.
+    pub surface_technique: Option<SurfaceTechnique>,
+}
+
+impl FreeFormAttributes {
+    /// Updates the attributes with a `cstype`, `deg`, `bmat`, `step`, `ctech` or `stech` statement.
+    pub(crate) fn parse(&mut self, stmt: &str, args: &[&str]) -> Result<(), ParseError> {
+        match stmt {
+            "cstype" => {
+                let (rational, ty) = match *args {
+                    ["rat", ty] => (true, ty),
+                    [ty] => (false, ty),
+                    _ => error!(WrongNumberOfArguments)
+                };
+
+                self.basis = Some(match ty {
+                    "bmatrix" => Basis::BMatrix,
+                    "bezier" => Basis::Bezier,
+                    "bspline" => Basis::BSpline,
+                    "cardinal" => Basis::Cardinal,
+                    "taylor" => Basis::Taylor,
+                    _ => error!(WrongTypeOfArguments, ty)
+                });
+                self.rational = rational;
+            }
+            "deg" => self.degree = match *args {
+                [u] => [n(u)?, 0],
+                [u, v] => [n(u)?, n(v)?],
+                _ => error!(WrongNumberOfArguments)
+            },
+            "bmat" => {
+                let axis = match args.first() {
+                    Some(&"u") => 0,
+                    Some(&"v") => 1,
+                    Some(param) => error!(WrongTypeOfArguments, param),
+                    None => error!(WrongNumberOfArguments)
+                };
+
+                // The size overflows for huge degrees, which cannot match the arguments anyway
+                let size = (self.degree[axis] as usize).checked_add(1);
+                let len = size.and_then(|size| size.checked_mul(size));
+                if len != Some(args.len() - 1) { error!(WrongNumberOfArguments) }
+
+                self.basis_matrix[axis] = args[1..].iter()
+                    .map(|&param| n(param))
+                    .collect::<Result<_, _>>()?;
+            }
+            "step" => self.step = match *args {
+                [u] => [n(u)?, 0],
+                [u, v] => [n(u)?, n(v)?],
+                _ => error!(WrongNumberOfArguments)
+            },
+            "ctech" => self.curve_technique = Some(match *args {
+                ["cparm", res] => CurveTechnique::ConstantParametric { resolution: n(res)? },
+                ["cspace", max_length] => CurveTechnique::ConstantSpace {
+                    max_length: n(max_length)?
+                },
+                ["curv", max_distance, max_angle] => CurveTechnique::Curvature {
+                    max_distance: n(max_distance)?,
+                    max_angle: n(max_angle)?
+                },
+                [technique, ..] if !["cparm", "cspace", "curv"].contains(&technique) => {
+                    error!(WrongTypeOfArguments, technique)
+                }
+                _ => error!(WrongNumberOfArguments)
+            }),
+            "stech" => self.surface_technique = Some(match *args {
+                ["cparma", u, v] => SurfaceTechnique::ConstantParametric {
+                    resolution: [n(u)?, n(v)?]
+                },
+                ["cparmb", uv] => SurfaceTechnique::ConstantParametricTrimmed {
+                    resolution: n(uv)?
+                },
+                ["cspace", max_length] => SurfaceTechnique::ConstantSpace {
+                    max_length: n(max_length)?
+                },
+                ["curv", max_distance, max_angle] => SurfaceTechnique::Curvature {
+                    max_distance: n(max_distance)?,
+                    max_angle: n(max_angle)?
+                },
+                [technique, ..] if !["cparma", "cparmb", "cspace", "curv"].contains(&technique) => {
+                    error!(WrongTypeOfArguments, technique)
+                }
+                _ => error!(WrongNumberOfArguments)
+            }),
+            _ => error!(UnexpectedStatement)
+        }
+
+        Ok(())
+    }
+}
+
+/// Type of the basis of a free-form curve or surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Basis {
+    /// Basis matrix, which is given by `bmat` statements.
+    BMatrix,
+    /// Bezier.
+    Bezier,
+    /// B-spline.
+    BSpline,
+    /// Cardinal spline, also known as Catmull-Rom spline.
+    Cardinal,
+    /// Taylor series.
+    Taylor,
+}
+
+/// Technique to approximate a curve with lines, which is set by a `ctech` statement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveTechnique {
+    /// Divides each knot span into `resolution` segments of the same parametric length.
+    ConstantParametric {
+        /// Number of segments per knot span.
+        resolution: f32
+    },
+    /// Divides the curve into segments no longer than `max_length` in object space.
+    ConstantSpace {
+        /// Maximum length of a segment.
+        max_length: f32
+    },
+    /// Divides the curve until each segment deviates from the curve by at most `max_distance`
+    /// and turns by at most `max_angle` degrees.
+    Curvature {
+        /// Maximum distance between a segment and the curve.
+        max_distance: f32,
+        /// Maximum angle in degrees between the tangents at both ends of a segment.
+        max_angle: f32
+    },
+}
+
+/// Technique to approximate a surface with polygons, which is set by a `stech` statement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SurfaceTechnique {
+    /// Divides each knot span into `resolution` segments in each direction, given by `cparma`.
+    ConstantParametric {
+        /// Number of segments per knot span, in the order of `u` and `v`.
+        resolution: [f32; 2]
+    },
+    /// Divides each knot span into `resolution` segments in both directions, given by `cparmb`.
+    ///
+    /// The grid is the same as `ConstantParametric` with an equal resolution in both directions.
+    ConstantParametricTrimmed {
+        /// Number of segments per knot span.
+        resolution: f32
+    },
+    /// Divides the surface into polygons whose edges are no longer than `max_length`.
+    ConstantSpace {
+        /// Maximum length of an edge.
+        max_length: f32
+    },
+    /// Divides the surface until each polygon deviates from the surface by at most
+    /// `max_distance` and its edges turn by at most `max_angle` degrees.
+    Curvature {
+        /// Maximum distance between a polygon and the surface.
+        max_distance: f32,
+        /// Maximum angle in degrees between the tangents at both ends of an edge.
+        max_angle: f32
+    },
+}
+
+
+/// A free-form curve in 3D space, which is declared by a `curv` statement.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    /// Free-form attributes which were in effect when the curve was declared.
+    pub attributes: FreeFormAttributes,
+    /// Starting and ending parameter values of the curve.
+    pub range: [f32; 2],
+    /// Indices of the control points in `Obj::vertices`.
+    pub control_points: Vec<u32>,
+    /// Global parameter values given by `parm u`, such as the knot vector of a B-spline.
+    pub parameters: Vec<f32>,
+    /// Indices of the special points in `Obj::param_vertices`.
+    pub special_points: Vec<u32>,
+}
+
+impl Curve {
+    /// Parses a `curv` statement. `count` is the number of vertices defined so far.
+    pub(crate) fn parse(args: &[&str], attributes: &FreeFormAttributes, count: usize)
+        -> Result<Self, ParseError>
+    {
+        if args.len() < 4 { error!(WrongNumberOfArguments) }
+
+        Ok(Curve {
+            attributes: attributes.clone(),
+            range: [n(args[0])?, n(args[1])?],
+            control_points: indices(&args[2..], count)?,
+            parameters: Vec::new(),
+            special_points: Vec::new(),
+        })
+    }
+
+    /// Parses a statement in the body of the curve, which is either `parm u` or `sp`. `counts`
+    /// are the numbers of the parameter vertices and the 2D curves defined so far.
+    pub(crate) fn parse_body(&mut self, stmt: &str, args: &[&str], counts: [usize; 2])
+        -> Result<(), ParseError>
+    {
+        let parameters = slice::from_mut(&mut self.parameters);
+        parse_common(stmt, args, parameters, &mut self.special_points, counts[0])
+    }
+}
+
+/// A free-form curve in the parameter space of a surface, which is declared by a `curv2`
+/// statement and referenced by `trim`, `hole` and `scrv` statements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve2D {
+    /// Free-form attributes which were in effect when the curve was declared.
+    pub attributes: FreeFormAttributes,
+    /// Indices of the control points in `Obj::param_vertices`.
+    pub control_points: Vec<u32>,
+    /// Global parameter values given by `parm u`, such as the knot vector of a B-spline.
+    pub parameters: Vec<f32>,
+    /// Indices of the special points in `Obj::param_vertices`.
+    pub special_points: Vec<u32>,
+}
+
+impl Curve2D {
+    /// Parses a `curv2` statement. `count` is the number of parameter vertices defined so far.
+    pub(crate) fn parse(args: &[&str], attributes: &FreeFormAttributes, count: usize)
+        -> Result<Self, ParseError>
+    {
+        if args.len() < 2 { error!(WrongNumberOfArguments) }
+
+        Ok(Curve2D {
+            attributes: attributes.clone(),
+            control_points: indices(args, count)?,
+            parameters: Vec::new(),
+            special_points: Vec::new(),
+        })
+    }
+
+    /// Parses a statement in the body of the 2D curve, in the same way as `Curve::parse_body`.
+    pub(crate) fn parse_body(&mut self, stmt: &str, args: &[&str], counts: [usize; 2])
+        -> Result<(), ParseError>
+    {
+        let parameters = slice::from_mut(&mut self.parameters);
+        parse_common(stmt, args, parameters, &mut self.special_points, counts[0])
+    }
+}
+
+/// A free-form surface, which is declared by a `surf` statement.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Surface {
+    /// Free-form attributes which were in effect when the surface was declared.
+    pub attributes: FreeFormAttributes,
+    /// Starting and ending parameter values of the surface, in the order of `u` and `v`.
+    pub range: [[f32; 2]; 2],
+    /// Control points, which are written in the same format as the vertices of a polygon.
+    pub control_points: Polygon,
+    /// Global parameter values given by `parm u` and `parm v`, such as the knot vectors of a
+    /// B-spline surface.
+    pub parameters: [Vec<f32>; 2],
+    /// Outer trimming loops given by `trim` statements.
+    pub trims: Vec<Vec<CurveSegment>>,
+    /// Inner trimming loops given by `hole` statements.
+    pub holes: Vec<Vec<CurveSegment>>,
+    /// Special curves given by `scrv` statements, which must be included in any triangulation.
+    pub special_curves: Vec<Vec<CurveSegment>>,
+    /// Indices of the special points in `Obj::param_vertices`.
+    pub special_points: Vec<u32>,
+}
+
+impl Surface {
+    /// Parses a `surf` statement. `counts` are the numbers of the vertices, texture coordinates
+    /// and normals defined so far.
+    pub(crate) fn parse(args: &[&str], attributes: &FreeFormAttributes, counts: [usize; 3],
+                        mixed: MixedFormats) -> Result<Self, ParseError>
+    {
+        if args.len() < 5 { error!(WrongNumberOfArguments) }
+
+        let mut corners = Vec::with_capacity(args.len() - 4);
+        for param in &args[4..] {
+            corners.push(corner(param, counts)?);
+        }
+
+        Ok(Surface {
+            attributes: attributes.clone(),
+            range: [[n(args[0])?, n(args[1])?], [n(args[2])?, n(args[3])?]],
+            control_points: polygon(&corners, &args[4..], mixed)?,
+            parameters: [Vec::new(), Vec::new()],
+            trims: Vec::new(),
+            holes: Vec::new(),
+            special_curves: Vec::new(),
+            special_points: Vec::new(),
+        })
+    }
+
+    /// Parses a statement in the body of the surface. `counts` are the numbers of the parameter
+    /// vertices and the 2D curves defined so far.
+    pub(crate) fn parse_body(&mut self, stmt: &str, args: &[&str], counts: [usize; 2])
+        -> Result<(), ParseError>
+    {
+        match stmt {
+            "trim" => self.trims.push(segments(args, counts[1])?),
+            "hole" => self.holes.push(segments(args, counts[1])?),
+            "scrv" => self.special_curves.push(segments(args, counts[1])?),
+            _ => {
+                let parameters = &mut self.parameters[..];
+                parse_common(stmt, args, parameters, &mut self.special_points, counts[0])?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A segment of a `Curve2D`, which is a part of a trimming loop or a special curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CurveSegment {
+    /// Starting and ending parameter values of the segment on the curve.
+    pub range: [f32; 2],
+    /// Index of the curve in `Obj::curves_2d`.
+    pub curve: usize,
+}
+
+/// Connectivity between two surfaces along a shared edge, which is declared by a `con`
+/// statement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Connection {
+    /// Indices of the two surfaces in `Obj::surfaces`.
+    pub surfaces: [usize; 2],
+    /// Segments of the curves in the parameter spaces of each surface, which meet at the edge.
+    pub curves: [CurveSegment; 2],
+}
+
+impl Connection {
+    /// Parses a `con` statement. `counts` are the numbers of the surfaces and the 2D curves
+    /// defined so far.
+    pub(crate) fn parse(args: &[&str], counts: [usize; 2]) -> Result<Self, ParseError> {
+        if args.len() != 8 { error!(WrongNumberOfArguments) }
+
+        let side = |args: &[&str]| -> Result<(usize, CurveSegment), ParseError> {
+            let segment = segments(&args[1..], counts[1])?[0];
+            Ok((index(args[0], counts[0])?, segment))
+        };
+        let (first, second) = (side(&args[..4])?, side(&args[4..])?);
+
+        Ok(Connection {
+            surfaces: [first.0, second.0],
+            curves: [first.1, second.1],
+        })
+    }
+}
+
+/// Parses references to one or more of the `count` vectors defined so far.
+fn indices(args: &[&str], count: usize) -> Result<Vec<u32>, ParseError> {
+    if args.is_empty() { error!(WrongNumberOfArguments) }
+    args.iter().map(|param| index(param, count)).collect()
+}
+
+/// Parses a `parm` or `sp` statement, which may appear in the body of any free-form element.
+/// `parameters` holds the parameter values of each axis which the element has, and `count` is
+/// the number of parameter vertices defined so far.
+fn parse_common(stmt: &str, args: &[&str], parameters: &mut [Vec<f32>],
+                special_points: &mut Vec<u32>, count: usize) -> Result<(), ParseError>
+{
+    match stmt {
+        "parm" => {
+            let (axis, values) = self::parameters(args)?;
+            match parameters.get_mut(axis) {
+                Some(parameters) => *parameters = values,
+                None => error!(WrongTypeOfArguments, args[0])
+            }
+        }
+        "sp" => special_points.extend(indices(args, count)?),
+        _ => error!(UnexpectedStatement)
+    }
+    Ok(())
+}
+
+/// Parses arguments of a `parm` statement into the axis, `0` for `u` and `1` for `v`, and the
+/// parameter values.
+fn parameters(args: &[&str]) -> Result<(usize, Vec<f32>), ParseError> {
+    let axis = match args.first() {
+        Some(&"u") => 0,
+        Some(&"v") => 1,
+        Some(param) => error!(WrongTypeOfArguments, param),
+        None => error!(WrongNumberOfArguments)
+    };
+    if args.len() < 3 { error!(WrongNumberOfArguments) }
+
+    let parameters = args[1..].iter().map(|&param| n(param)).collect::<Result<_, _>>()?;
+    Ok((axis, parameters))
+}
+
+/// Parses arguments of a `trim`, `hole` or `scrv` statement, which are triples of starting and
+/// ending parameter values and a reference to one of the `count` 2D curves defined so far.
+fn segments(args: &[&str], count: usize) -> Result<Vec<CurveSegment>, ParseError> {
+    if args.is_empty() || !args.len().is_multiple_of(3) { error!(WrongNumberOfArguments) }
+
+    args.chunks(3).map(|segment| Ok(CurveSegment {
+        range: [n(segment[0])?, n(segment[1])?],
+        curve: index(segment[2], count)?,
+    })).collect()
+}
+
+#[test]
+fn test_attributes() {
+    let mut attributes = FreeFormAttributes::default();
+    let mut parse = |stmt, args: &[&str]| attributes.parse(stmt, args).map_err(|e| e.kind);
+
+    assert!(parse("cstype", &["rat", "bspline"]).is_ok());
+    assert!(parse("deg", &["1", "2"]).is_ok());
+    assert!(parse("bmat", &["u", "1", "0", "-1", "1"]).is_ok());
+    assert!(parse("step", &["1"]).is_ok());
+
+    use crate::error::ParseErrorKind::*;
+    assert_eq!(parse("cstype", &["nurbs"]), Err(WrongTypeOfArguments));
+    assert_eq!(parse("deg", &[]), Err(WrongNumberOfArguments));
+    assert_eq!(parse("bmat", &["w", "1"]), Err(WrongTypeOfArguments));
+    assert_eq!(parse("bmat", &["v", "1", "0", "0"]), Err(WrongNumberOfArguments));
+
+    assert!(parse("ctech", &["cspace", "0.5"]).is_ok());
+    assert!(parse("stech", &["cparma", "4", "8"]).is_ok());
+    assert_eq!(parse("stech", &["adaptive", "1"]), Err(WrongTypeOfArguments));
+    assert_eq!(parse("ctech", &["curv", "0.1"]), Err(WrongNumberOfArguments));
+
+    assert_eq!(attributes, FreeFormAttributes {
+        rational: true,
+        basis: Some(Basis::BSpline),
+        degree: [1, 2],
+        basis_matrix: [vec![1.0, 0.0, -1.0, 1.0], vec![]],
+        step: [1, 0],
+        curve_technique: Some(CurveTechnique::ConstantSpace { max_length: 0.5 }),
+        surface_technique: Some(SurfaceTechnique::ConstantParametric { resolution: [4.0, 8.0] }),
+    });
+
+    let mut attributes = FreeFormAttributes::default();
+    assert!(attributes.parse("deg", &["4294967295"]).is_ok());
+    assert_eq!(attributes.parse("bmat", &["u", "1"]).map_err(|e| e.kind),
+               Err(WrongNumberOfArguments));
+}
@@ -1,79 +1,236 @@
-use std::old_io::IoError;
-use error::ParseError;
+use std::io::BufRead;
+use std::str::FromStr;
+use crate::error::{ObjResult, ParseError};
 
-pub fn lex<T, F>(input: &mut T, mut callback: F) -> Option<IoError>
-    where T: Buffer, F: FnMut(&str, &[&str]) -> Option<ParseError>
+/// Splits `input` into statements and passes each of them to `callback`, along with the number of
+/// the line which contains it, starting from 1.
+///
+/// If `warnings` is `None`, lexing stops at the first error returned by `callback`. Otherwise the
+/// error is pushed into `warnings` and the offending statement is skipped.
+pub fn lex<T, F>(input: &mut T, mut warnings: Option<&mut Vec<ParseError>>, mut callback: F)
+    -> ObjResult<()>
+    where T: BufRead, F: FnMut(&str, &[&str], usize) -> Result<(), ParseError>
 {
-    for maybe_line in input.lines() {
-        match maybe_line {
-            Ok(line) => {
-                let line = &line.split('#').next().unwrap();
-
-                let mut words = line.words();
-                match words.next() {
-                    Some(stmt) => {
-                        let args: Vec<&str> = words.collect();
-                        let ret = callback(stmt, &args[]);
-
-                        if ret.is_some() {
-                            unimplemented!()
-                        }
-                    }
-                    None => {}
+    let mut line_number = 0;
+    let mut offset = 0;
+    let mut raw = String::new();
+    let mut first = String::new();
+    let mut joined = String::new();
+
+    loop {
+        raw.clear();
+        if input.read_line(&mut raw)? == 0 { break }
+        line_number += 1;
+
+        // Lines ending in `\` continue on the next line. The joined statement is reported at its
+        // first physical line.
+        let (start_line, start_offset) = (line_number, offset);
+        first.clear();
+        first.push_str(raw.trim_end_matches(['\n', '\r']));
+        joined.clear();
+        let mut head = None;
+        loop {
+            offset += raw.len();
+
+            let text = raw.trim_end_matches(['\n', '\r']);
+            let line = text.split('#').next().unwrap();
+            let continued = match line.trim_end().strip_suffix('\\') {
+                Some(part) => {
+                    joined.push_str(part);
+                    joined.push(' ');
+                    true
+                }
+                None => {
+                    joined.push_str(line);
+                    false
+                }
+            };
+            head.get_or_insert(joined.len());
+
+            if !continued { break }
+            raw.clear();
+            if input.read_line(&mut raw)? == 0 { break }
+            line_number += 1;
+        }
+
+        let mut words = joined.split_whitespace();
+        if let Some(stmt) = words.next() {
+            let args: Vec<&str> = words.collect();
+            if let Err(mut e) = callback(stmt, &args[..], start_line) {
+                locate(&mut e, &joined, &first, head.unwrap(), stmt, &args[..]);
+                e.line = start_line;
+                e.offset += start_offset;
+
+                match warnings {
+                    Some(ref mut warnings) => warnings.push(e),
+                    None => return Err(e.into()),
                 }
             }
-            Err(e) => { return Some(e); }
         }
     }
-    None
+    Ok(())
+}
+
+/// Parses a single argument, such as a number or an index.
+pub fn n<T: FromStr>(input: &str) -> Result<T, ParseError> {
+    match input.parse() {
+        Ok(number) => Ok(number),
+        Err(_) => error!(WrongTypeOfArguments, input)
+    }
+}
+
+/// Parses a reference to one of the `count` vectors which have been defined so far. It is either
+/// an absolute index starting from 1, or an index relative to the end of the list such as `-1`.
+/// Relative indices are resolved into absolute ones.
+///
+/// `0` never references a vector, so it is rejected here and reported at its statement. Positive
+/// indices are not checked against `count`, since vectors may be defined after their use, and
+/// are left to `Obj::validate`.
+pub fn index<T: TryFrom<usize>>(input: &str, count: usize) -> Result<T, ParseError> {
+    let absolute = match n::<isize>(input)? {
+        i if i > 0 => i as usize,
+        i if i < 0 && i.unsigned_abs() <= count => count + 1 - i.unsigned_abs(),
+        _ => error!(WrongTypeOfArguments, input)
+    };
+
+    match T::try_from(absolute) {
+        Ok(index) => Ok(index),
+        Err(_) => error!(WrongTypeOfArguments, input)
+    }
+}
+
+/// Fills the location of `error` within `text`, which is the statement containing `stmt` and
+/// `args`. The statement may span several physical lines, of which `first` is the first one and
+/// makes up the first `head` bytes of `text`.
+fn locate(error: &mut ParseError, text: &str, first: &str, head: usize, stmt: &str, args: &[&str]) {
+    // `stmt` and `args` are slices of `text`, so their positions can be calculated from addresses
+    let position = |word: &str| word.as_ptr() as usize - text.as_ptr() as usize;
+
+    let mut start = position(stmt);
+    if let Some(ref token) = error.token {
+        // An argument equal to the token takes precedence over one which merely contains it, such
+        // as `10` for `0`. Substrings are still matched for components like the `x` of `1/x`.
+        let exact = args.iter().find(|arg| **arg == token);
+        let found = exact.map(|arg| position(arg)).or_else(|| {
+            args.iter().find_map(|arg| arg.find(&token[..]).map(|index| position(arg) + index))
+        });
+        if let Some(found) = found {
+            start = found;
+        }
+    }
+
+    // Tokens on continuation lines are reported at the statement
+    if start >= head {
+        start = position(stmt).min(head);
+    }
+
+    error.column = first[..start].chars().count() + 1;
+    error.offset = start;
+    error.stmt = stmt.to_string();
+    error.text = first.to_string();
 }
 
 #[test]
 fn test_lex() {
-    use error::{parse_error, ParseErrorKind};
+    use crate::error::{parse_error, ParseErrorKind};
 
     let input = r#"
    statement0      arg0  arg1	arg2#argX   argX
 statement1 arg0    arg1
 # Comment
 statement2 Hello, world!
+statement3 arg0 \
+  arg1 # Comment \
+statement4 arg0\
+arg1
 "#;
 
-    lex(&mut input.as_bytes(), |stmt, args| {
+    let result = lex(&mut input.as_bytes(), None, |stmt, args, line| {
         match stmt {
-            "statement0" => assert_eq!(args, ["arg0", "arg1", "arg2"]),
-            "statement1" => assert_eq!(args, ["arg0", "arg1"]),
-            "statement2" => assert_eq!(args, ["Hello,", "world!"]),
-            _ => return Some(parse_error(ParseErrorKind::UnexpectedStatement))
+            "statement0" => assert_eq!((args, line), (&["arg0", "arg1", "arg2"][..], 2)),
+            "statement1" => assert_eq!((args, line), (&["arg0", "arg1"][..], 3)),
+            "statement2" => assert_eq!((args, line), (&["Hello,", "world!"][..], 5)),
+            "statement3" => assert_eq!((args, line), (&["arg0", "arg1"][..], 6)),
+            "statement4" => assert_eq!((args, line), (&["arg0", "arg1"][..], 8)),
+            _ => return Err(parse_error(ParseErrorKind::UnexpectedStatement))
         }
-        None
+        Ok(())
     });
+    assert!(result.is_ok());
 }
 
-#[cfg(test)]
-mod bench {
-    //! There is a slight overhead (~30ns) in `lex()` function because it passes arguments as a
-    //! slice not an iterator.
+#[test]
+fn test_lex_location() {
+    use crate::error::ObjError;
 
-    extern crate test;
+    let input = "first line\n\tv 1.0 abc 2.0 # comment\n";
 
-    #[bench]
-    fn pass_slice(b: &mut test::Bencher) {
-        b.iter(|| {
-            let words = "1.00 2.00 3.00".words();
-            let args: Vec<&str> = words.collect();
-            let args = &args[];
+    let result = lex(&mut input.as_bytes(), None, |stmt, args, _| {
+        match stmt {
+            "v" => error!(WrongTypeOfArguments, args[1]),
+            _ => Ok(())
+        }
+    });
 
-            args.iter().map(|&input| input.parse::<f32>().unwrap()).collect::<Vec<f32>>();
-        })
+    match result {
+        Err(ObjError::Parse(e)) => {
+            assert_eq!(e.line, 2);
+            assert_eq!(e.column, 8);
+            assert_eq!(e.offset, 18);
+            assert_eq!(e.stmt, "v");
+            assert_eq!(e.token, Some("abc".to_string()));
+            assert_eq!(e.text, "\tv 1.0 abc 2.0 # comment");
+            assert_eq!(e.to_string(), "error: Wrong type of arguments\n \
+                                       --> 2:8\n  |\n2 | \tv 1.0 abc 2.0 # comment\n  | \t      ^^^");
+        }
+        _ => panic!()
     }
 
-    #[bench]
-    fn pass_iter(b: &mut test::Bencher) {
-        b.iter(|| {
-            let words = "1.00 2.00 3.00".words();
+    // The argument `0` is located rather than the `0` inside `10`
+    let result = lex(&mut "f 10 2 0\n".as_bytes(), None, |_, args, _| {
+        error!(WrongTypeOfArguments, args[2])
+    });
+    match result {
+        Err(ObjError::Parse(e)) => assert_eq!(e.column, 8),
+        _ => panic!()
+    }
 
-            words.map(|input| input.parse::<f32>().unwrap()).collect::<Vec<f32>>();
-        })
+    // Errors on continuation lines are reported at the first physical line of the statement
+    let input = "first line\nv 1.0 \\\n  abc 2.0\n";
+    let result = lex(&mut input.as_bytes(), None, |stmt, args, _| {
+        match stmt {
+            "v" => error!(WrongTypeOfArguments, args[1]),
+            _ => Ok(())
+        }
+    });
+    match result {
+        Err(ObjError::Parse(e)) => {
+            assert_eq!(e.line, 2);
+            assert_eq!(e.column, 1);
+            assert_eq!(e.offset, 11);
+            assert_eq!(e.text, "v 1.0 \\");
+        }
+        _ => panic!()
     }
 }
+
+#[test]
+fn test_lex_warnings() {
+    use crate::error::{parse_error, ParseErrorKind};
+
+    let input = "good\nbad 0\ngood\nbad 1\n";
+    let mut warnings = Vec::new();
+    let mut count = 0;
+
+    let result = lex(&mut input.as_bytes(), Some(&mut warnings), |stmt, _, _| {
+        match stmt {
+            "good" => { count += 1; Ok(()) }
+            _ => Err(parse_error(ParseErrorKind::UnexpectedStatement))
+        }
+    });
+
+    assert!(result.is_ok());
+    assert_eq!(count, 2);
+    assert_eq!(warnings.iter().map(|e| e.line).collect::<Vec<_>>(), [2, 4]);
+    assert_eq!(warnings[1].text, "bad 1");
+}
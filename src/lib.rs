@@ -4,13 +4,21 @@
 
 #![deny(warnings, missing_docs)]
 
-#![feature(core, collections, io)]
-#![cfg_attr(test, feature(test))]
-
-#[macro_use] mod error;
+#[macro_use] pub mod error;
 mod lex;
 pub mod obj;
+pub mod freeform;
+mod nurbs;
+pub mod curve;
+pub mod tessellate;
 pub mod mtl;
+pub mod loader;
+pub mod resolve;
 
-pub use obj::{load_obj, Obj};
-pub use mtl::{load_mtl, Mtl};
+pub use obj::{load_obj, load_obj_with_options, Obj, LoadOptions, Strictness};
+pub use mtl::{load_mtl, load_mtl_with_options, Mtl, Material, ColorSpec, IlluminationModel, TextureMap};
+pub use tessellate::{tessellate, TessellateOptions, Tessellation};
+pub use curve::CurveEvaluator;
+pub use loader::{load_obj_with_materials, load_obj_with_resolver, LoadedObj};
+pub use resolve::{ResourceResolver, FileResolver, MemoryResolver};
+pub use error::{ObjError, ObjResult};
@@ -0,0 +1,144 @@
+//! Loads an `.obj` file along with the `.mtl` files and other files which it references
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+use crate::error::{parse_error, ObjResult, ParseError, ParseErrorKind};
+use crate::obj::{load_obj_with_options, LoadOptions, Obj};
+use crate::mtl::{load_mtl_with_options, Material, Mtl};
+use crate::resolve::{FileResolver, ResourceResolver};
+
+/// Loads the `.obj` file at `path` from the filesystem, along with every material library
+/// referenced by its `mtllib` statements and every file included by its `call` statements.
+///
+/// See `load_obj_with_resolver` for details.
+pub fn load_obj_with_materials<P>(path: P, options: &LoadOptions) -> ObjResult<LoadedObj>
+    where P: AsRef<Path>
+{
+    load_obj_with_resolver(&FileResolver, &path.as_ref().to_string_lossy(), options)
+}
+
+/// Loads the `.obj` resource at `path` from `resolver`, along with every material library
+/// referenced by its `mtllib` statements and every file included by its `call` statements.
+///
+/// References are resolved with `ResourceResolver::resolve`, so that they are relative to the
+/// file which contains them. Paths of the texture maps in the loaded materials are replaced with
+/// the resolved paths, which can be passed to `ResourceResolver::open` directly.
+///
+/// Resources which cannot be opened, materials which are not defined in any library and
+/// recursive `call` statements are reported as warnings, instead of failing the whole load.
+pub fn load_obj_with_resolver<R>(resolver: &R, path: &str, options: &LoadOptions)
+    -> ObjResult<LoadedObj> where R: ResourceResolver
+{
+    let input = resolver.open(path)?;
+    load(resolver, path, input, options, &mut Vec::new())
+}
+
+/// Loads the already opened `.obj` resource at `path`. `stack` holds the paths of the resources
+/// which are being loaded, to detect recursive `call` statements.
+fn load<R, T>(resolver: &R, path: &str, input: T, options: &LoadOptions, stack: &mut Vec<String>)
+    -> ObjResult<LoadedObj> where R: ResourceResolver, T: BufRead
+{
+    let (obj, obj_warnings) = load_obj_with_options(input, options)?;
+    let mut warnings: Vec<_> = obj_warnings.into_iter().map(|e| (path.to_string(), e)).collect();
+
+    let mut materials = Mtl::default();
+    for library in obj.material_libraries.iter() {
+        let library_path = resolver.resolve(path, library);
+        let mut input = match resolver.open(&library_path) {
+            Ok(input) => input,
+            Err(_) => {
+                let kind = ParseErrorKind::MissingMaterialLibrary;
+                warnings.push((path.to_string(), diagnostic(kind, "mtllib", library)));
+                continue
+            }
+        };
+
+        let (mut mtl, mtl_warnings) = load_mtl_with_options(&mut input, options)?;
+        for material in mtl.materials.iter_mut() {
+            for map in material.texture_maps_mut() {
+                map.path = resolver.resolve(&library_path, &map.path);
+            }
+        }
+        materials.materials.extend(mtl.materials);
+        warnings.extend(mtl_warnings.into_iter().map(|e| (library_path.clone(), e)));
+    }
+
+    let mut mesh_materials = HashMap::new();
+    for (name, _) in obj.material_meshes() {
+        match materials.materials.iter().position(|material| material.name == *name) {
+            Some(index) => { mesh_materials.insert(name.to_string(), index); }
+            None => {
+                let kind = ParseErrorKind::UndefinedMaterial;
+                warnings.push((path.to_string(), diagnostic(kind, "usemtl", name)));
+            }
+        }
+    }
+
+    stack.push(path.to_string());
+    let mut calls = Vec::new();
+    for call in obj.calls.iter() {
+        let call_path = resolver.resolve(path, &call.path);
+        if stack.contains(&call_path) {
+            let kind = ParseErrorKind::RecursiveCall;
+            warnings.push((path.to_string(), diagnostic(kind, "call", &call.path)));
+            continue
+        }
+
+        match resolver.open(&call_path) {
+            Ok(input) => calls.push(load(resolver, &call_path, input, options, stack)?),
+            Err(_) => {
+                let kind = ParseErrorKind::MissingCallTarget;
+                warnings.push((path.to_string(), diagnostic(kind, "call", &call.path)));
+            }
+        }
+    }
+    stack.pop();
+
+    Ok(LoadedObj {
+        path: path.to_string(),
+        obj,
+        materials,
+        mesh_materials,
+        calls,
+        warnings,
+    })
+}
+
+/// Constructs a warning about `name`, which was referenced by a `stmt` statement.
+fn diagnostic(kind: ParseErrorKind, stmt: &str, name: &str) -> ParseError {
+    let mut error = parse_error(kind);
+    error.stmt = stmt.to_string();
+    error.token = Some(name.to_string());
+    error.text = format!("{} {}", stmt, name);
+    error
+}
+
+
+/// An `Obj` along with the materials and the files which it references.
+pub struct LoadedObj {
+    /// Resolved path of the `.obj` resource.
+    pub path: String,
+    /// The loaded object.
+    pub obj: Obj,
+    /// Materials of every material library, in the order of the `mtllib` statements.
+    pub materials: Mtl,
+    /// Index into `materials.materials` for each mesh of `obj.material_meshes` whose material is
+    /// defined.
+    pub mesh_materials: HashMap<String, usize>,
+    /// Files included by the `call` statements of `obj`, in the same order.
+    ///
+    /// Arguments of the `call` statements are not substituted into the included files.
+    pub calls: Vec<LoadedObj>,
+    /// Statements skipped in lenient mode and the references which could not be resolved, along
+    /// with the resolved path of the resource which contains each of them. Warnings of the
+    /// material libraries are located within the library, not within this file.
+    pub warnings: Vec<(String, ParseError)>,
+}
+
+impl LoadedObj {
+    /// Returns the material of the mesh which was started with `usemtl mesh`.
+    pub fn material(&self, mesh: &str) -> Option<&Material> {
+        self.mesh_materials.get(mesh).map(|&index| &self.materials.materials[index])
+    }
+}
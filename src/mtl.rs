@@ -1,51 +1,610 @@
 //! Parses `.mtl` format which stores material data
 
-use lex::lex;
-use error::{parse_error, ParseErrorKind};
+use std::io::BufRead;
+use std::str::FromStr;
+use crate::lex::{lex, n};
+use crate::error::{ObjResult, ParseError};
+use crate::obj::{LoadOptions, Strictness};
+
+/// Parses a wavefront `.mtl` format
+pub fn load_mtl<T: BufRead>(input: &mut T) -> ObjResult<Mtl> {
+    load_mtl_with_options(input, &Default::default()).map(|(mtl, _)| mtl)
+}
+
+/// Parses a wavefront `.mtl` format with the given options.
+///
+/// Returns the parsed `Mtl` along with the statements which were skipped in
+/// `Strictness::Lenient` mode.
+pub fn load_mtl_with_options<T: BufRead>(input: &mut T, options: &LoadOptions)
+    -> ObjResult<(Mtl, Vec<ParseError>)>
+{
+    let mut warnings = Vec::new();
+    let mut materials: Vec<Material> = Vec::new();
+
+    let warnings_or_none = match options.strictness {
+        Strictness::Strict => None,
+        Strictness::Lenient => Some(&mut warnings),
+    };
+
+    lex(input, warnings_or_none, |stmt, args, _| {
+        // Every statement except `newmtl` modifies the most recently declared material
+        macro_rules! m {
+            () => (match materials.last_mut() {
+                Some(material) => material,
+                None => error!(UnexpectedStatement)
+            })
+        }
 
-/// Parses a wavefront `.obj` format *(incomplete)*
-pub fn load_mtl<T: Buffer>(input: &mut T) -> Mtl {
-    lex(input, |stmt, _| {
         match stmt {
             // Material name statement
-            "newmtl" => unimplemented!(),
+            "newmtl" => match args {
+                [] => error!(WrongNumberOfArguments),
+                _ => materials.push(Material::new(args.join(" ")))
+            },
 
             // Material color and illumination statements
-            "Ka" => unimplemented!(),
-            "Kd" => unimplemented!(),
-            "Ks" => unimplemented!(),
-            "Ke" => unimplemented!(),
-            "Km" => unimplemented!(),
-            "Ns" => unimplemented!(),
-            "Ni" => unimplemented!(),
-            "Tr" => unimplemented!(),
-            "Tf" => unimplemented!(),
-            "illum" => unimplemented!(),
-            "d" => unimplemented!(),
+            "Ka" => m!().ka = Some(color(args)?),
+            "Kd" => m!().kd = Some(color(args)?),
+            "Ks" => m!().ks = Some(color(args)?),
+            "Ke" => m!().ke = Some(color(args)?),
+            "Km" => m!().km = Some(color(args)?),
+            "Ns" => m!().ns = Some(scalar(args)?),
+            "Ni" => m!().ni = Some(scalar(args)?),
+            "Tr" => m!().d = Some(1.0 - scalar::<f32>(args)?),
+            "Tf" => m!().tf = Some(color(args)?),
+            "illum" => m!().illum = Some(IlluminationModel::from_u32(scalar(args)?)),
+            "d" => match args {
+                ["-halo", factor] => {
+                    let material = m!();
+                    material.d = Some(n(factor)?);
+                    material.halo = true;
+                }
+                _ => m!().d = Some(scalar(args)?),
+            },
+            "sharpness" => m!().sharpness = Some(scalar(args)?),
+
+            // Physically-based rendering extension statements
+            "Pr" => m!().pr = Some(scalar(args)?),
+            "Pm" => m!().pm = Some(scalar(args)?),
+            "Ps" => m!().ps = Some(scalar(args)?),
+            "Pc" => m!().pc = Some(scalar(args)?),
+            "Pcr" => m!().pcr = Some(scalar(args)?),
+            "aniso" => m!().aniso = Some(scalar(args)?),
+            "anisor" => m!().anisor = Some(scalar(args)?),
 
             // Texture map statements
-            "map_Ka" => unimplemented!(),
-            "map_Kd" => unimplemented!(),
-            "map_Ks" => unimplemented!(),
-            "map_d" => unimplemented!(),
-            "map_aat" => unimplemented!(),
-            "map_refl" => unimplemented!(),
-            "map_bump" | "map_Bump" | "bump" => unimplemented!(),
-            "disp" => unimplemented!(),
+            "map_Ka" => m!().map_ka = Some(texture(args)?),
+            "map_Kd" => m!().map_kd = Some(texture(args)?),
+            "map_Ks" => m!().map_ks = Some(texture(args)?),
+            "map_Ns" => m!().map_ns = Some(texture(args)?),
+            "map_d" => m!().map_d = Some(texture(args)?),
+            "map_aat" => m!().map_aat = match args {
+                ["on"] => true,
+                ["off"] => false,
+                [param] => error!(WrongTypeOfArguments, param),
+                _ => error!(WrongNumberOfArguments)
+            },
+            "map_bump" | "map_Bump" | "bump" => m!().map_bump = Some(texture(args)?),
+            "disp" => m!().disp = Some(texture(args)?),
+            "decal" => m!().decal = Some(texture(args)?),
+            "map_Ke" => m!().map_ke = Some(texture(args)?),
+            "map_Pr" => m!().map_pr = Some(texture(args)?),
+            "map_Pm" => m!().map_pm = Some(texture(args)?),
+            "map_Ps" => m!().map_ps = Some(texture(args)?),
+            "norm" => m!().norm = Some(texture(args)?),
 
             // Reflection map statement
-            "refl" => unimplemented!(),
+            "refl" | "map_refl" => m!().refl.push(texture(args)?),
 
             // Unexpected statement
             _ => error!(UnexpectedStatement)
         }
 
-        None
-    });
+        Ok(())
+    })?;
+
+    Ok((Mtl { materials }, warnings))
+}
+
+/// Parses arguments of a color statement, which is one of the following forms.
+///
+/// - `r [g b]`
+/// - `spectral file.rfl [factor]`
+/// - `xyz x [y z]`
+fn color(args: &[&str]) -> Result<ColorSpec, ParseError> {
+    match args {
+        ["spectral", file] => Ok(ColorSpec::Spectral { file: file.to_string(), factor: 1.0 }),
+        ["spectral", file, factor] => {
+            Ok(ColorSpec::Spectral { file: file.to_string(), factor: n(factor)? })
+        }
+        ["xyz", rest @ ..] => Ok(ColorSpec::Xyz(triple(rest)?)),
+        _ => Ok(ColorSpec::Rgb(triple(args)?))
+    }
+}
+
+/// Parses three numbers. The last two default to the first one if they are omitted.
+fn triple(args: &[&str]) -> Result<[f32; 3], ParseError> {
+    match args {
+        [x] => {
+            let x = n(x)?;
+            Ok([x, x, x])
+        }
+        [x, y, z] => Ok([n(x)?, n(y)?, n(z)?]),
+        _ => error!(WrongNumberOfArguments)
+    }
+}
+
+/// Parses arguments of a statement which has a single numeric argument.
+fn scalar<T: FromStr>(args: &[&str]) -> Result<T, ParseError> {
+    match args {
+        [param] => n(param),
+        _ => error!(WrongNumberOfArguments)
+    }
+}
+
+/// Parses arguments of a texture map statement, which are options followed by a path. The path
+/// may contain spaces.
+fn texture(args: &[&str]) -> Result<TextureMap, ParseError> {
+    let mut map = TextureMap::default();
+    let mut i = 0;
+
+    while i < args.len() && args[i].starts_with('-') {
+        let option = args[i];
+        i += 1;
+
+        match option {
+            "-blendu" => map.blendu = switch(args, &mut i)?,
+            "-blendv" => map.blendv = switch(args, &mut i)?,
+            "-bm" => map.bm = n(arg(args, &mut i)?)?,
+            "-boost" => map.boost = Some(n(arg(args, &mut i)?)?),
+            "-cc" => map.cc = switch(args, &mut i)?,
+            "-clamp" => map.clamp = switch(args, &mut i)?,
+            "-imfchan" => map.imfchan = Some(match arg(args, &mut i)? {
+                "r" => Channel::R,
+                "g" => Channel::G,
+                "b" => Channel::B,
+                "m" => Channel::M,
+                "l" => Channel::L,
+                "z" => Channel::Z,
+                param => error!(WrongTypeOfArguments, param)
+            }),
+            "-mm" => {
+                map.mm[0] = n(arg(args, &mut i)?)?;
+                map.mm[1] = n(arg(args, &mut i)?)?;
+            }
+            "-o" => vector(args, &mut i, &mut map.o)?,
+            "-s" => vector(args, &mut i, &mut map.s)?,
+            "-t" => vector(args, &mut i, &mut map.t)?,
+            "-texres" => map.texres = Some(n(arg(args, &mut i)?)?),
+            "-type" => map.ty = Some(match arg(args, &mut i)? {
+                "sphere" => ReflectionType::Sphere,
+                "cube_top" => ReflectionType::CubeTop,
+                "cube_bottom" => ReflectionType::CubeBottom,
+                "cube_front" => ReflectionType::CubeFront,
+                "cube_back" => ReflectionType::CubeBack,
+                "cube_left" => ReflectionType::CubeLeft,
+                "cube_right" => ReflectionType::CubeRight,
+                param => error!(WrongTypeOfArguments, param)
+            }),
+            _ => error!(WrongTypeOfArguments, option)
+        }
+    }
+
+    if i == args.len() { error!(WrongNumberOfArguments) }
+    map.path = args[i..].join(" ");
+    Ok(map)
+}
+
+/// Takes the next argument of a texture map option.
+fn arg<'a>(args: &[&'a str], i: &mut usize) -> Result<&'a str, ParseError> {
+    if *i == args.len() { error!(WrongNumberOfArguments) }
+    *i += 1;
+    Ok(args[*i - 1])
+}
+
+/// Takes the next argument of a texture map option which is either `on` or `off`.
+fn switch(args: &[&str], i: &mut usize) -> Result<bool, ParseError> {
+    match arg(args, i)? {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        param => error!(WrongTypeOfArguments, param)
+    }
+}
+
+/// Takes one to three numeric arguments of a texture map option such as `-o u [v [w]]`.
+/// Components which are omitted keep their values in `vector`.
+fn vector(args: &[&str], i: &mut usize, vector: &mut [f32; 3]) -> Result<(), ParseError> {
+    vector[0] = n(arg(args, i)?)?;
+
+    // Optional components are taken only if they are numbers, and the path must remain
+    for component in vector[1..].iter_mut() {
+        if *i + 1 >= args.len() { break }
+        match args[*i].parse() {
+            Ok(value) => *component = value,
+            Err(_) => break
+        }
+        *i += 1;
+    }
+    Ok(())
+}
+
+
+/// Low-level Rust binding for `.mtl` format.
+#[derive(Clone, Debug, Default)]
+pub struct Mtl {
+    /// Materials in the order of their declaration.
+    pub materials: Vec<Material>,
+}
+
+impl Mtl {
+    /// Returns the material named `name`. If there are several, the first one is returned.
+    pub fn get(&self, name: &str) -> Option<&Material> {
+        self.materials.iter().find(|material| material.name == name)
+    }
+}
+
+/// A single material declared with the `newmtl` statement.
+///
+/// Every field is `None` unless the corresponding statement was present.
+#[derive(Clone, Debug, Default)]
+pub struct Material {
+    /// Name of the material.
+    pub name: String,
+
+    /// Ambient color. (`Ka`)
+    pub ka: Option<ColorSpec>,
+    /// Diffuse color. (`Kd`)
+    pub kd: Option<ColorSpec>,
+    /// Specular color. (`Ks`)
+    pub ks: Option<ColorSpec>,
+    /// Emissive color. (`Ke`)
+    pub ke: Option<ColorSpec>,
+    /// Bump multiplier of older exporters. (`Km`)
+    pub km: Option<ColorSpec>,
+    /// Specular exponent. (`Ns`)
+    pub ns: Option<f32>,
+    /// Optical density, also known as the index of refraction. (`Ni`)
+    pub ni: Option<f32>,
+    /// Dissolve factor, where `1.0` is fully opaque. (`d`, or `1 - Tr`)
+    pub d: Option<f32>,
+    /// Whether the dissolve depends on the surface orientation. (`d -halo`)
+    pub halo: bool,
+    /// Transmission filter. (`Tf`)
+    pub tf: Option<ColorSpec>,
+    /// Illumination model. (`illum`)
+    pub illum: Option<IlluminationModel>,
+    /// Sharpness of the reflections. (`sharpness`)
+    pub sharpness: Option<f32>,
 
-    Mtl
+    /// Roughness of the physically-based material. (`Pr`)
+    pub pr: Option<f32>,
+    /// Metallic factor of the physically-based material. (`Pm`)
+    pub pm: Option<f32>,
+    /// Sheen of the physically-based material. (`Ps`)
+    pub ps: Option<f32>,
+    /// Clearcoat thickness of the physically-based material. (`Pc`)
+    pub pc: Option<f32>,
+    /// Clearcoat roughness of the physically-based material. (`Pcr`)
+    pub pcr: Option<f32>,
+    /// Anisotropy of the physically-based material. (`aniso`)
+    pub aniso: Option<f32>,
+    /// Rotation of the anisotropy of the physically-based material. (`anisor`)
+    pub anisor: Option<f32>,
+
+    /// Ambient color texture map. (`map_Ka`)
+    pub map_ka: Option<TextureMap>,
+    /// Diffuse color texture map. (`map_Kd`)
+    pub map_kd: Option<TextureMap>,
+    /// Specular color texture map. (`map_Ks`)
+    pub map_ks: Option<TextureMap>,
+    /// Specular exponent texture map. (`map_Ns`)
+    pub map_ns: Option<TextureMap>,
+    /// Dissolve texture map. (`map_d`)
+    pub map_d: Option<TextureMap>,
+    /// Whether anti-aliasing of textures is enabled. (`map_aat`)
+    pub map_aat: bool,
+    /// Bump map. (`bump`, `map_bump`)
+    pub map_bump: Option<TextureMap>,
+    /// Displacement map. (`disp`)
+    pub disp: Option<TextureMap>,
+    /// Decal texture map. (`decal`)
+    pub decal: Option<TextureMap>,
+    /// Reflection maps. A cube map is declared with six `refl` statements. (`refl`)
+    pub refl: Vec<TextureMap>,
+    /// Emissive color texture map. (`map_Ke`)
+    pub map_ke: Option<TextureMap>,
+    /// Roughness texture map. (`map_Pr`)
+    pub map_pr: Option<TextureMap>,
+    /// Metallic texture map. (`map_Pm`)
+    pub map_pm: Option<TextureMap>,
+    /// Sheen texture map. (`map_Ps`)
+    pub map_ps: Option<TextureMap>,
+    /// Normal map. (`norm`)
+    pub norm: Option<TextureMap>,
 }
 
-/// Low-level Rust binding for `.mtl` format *(incomplete)*.
-#[derive(Copy)]
-pub struct Mtl;
+impl Material {
+    /// Constructs an empty material named `name`.
+    pub fn new(name: String) -> Self {
+        Material { name, ..Default::default() }
+    }
+
+    /// Returns every texture map of the material.
+    pub fn texture_maps(&self) -> Vec<&TextureMap> {
+        let maps = [
+            &self.map_ka, &self.map_kd, &self.map_ks, &self.map_ns, &self.map_d, &self.map_bump,
+            &self.disp, &self.decal, &self.map_ke, &self.map_pr, &self.map_pm, &self.map_ps,
+            &self.norm,
+        ];
+
+        let mut result: Vec<&TextureMap> = maps.iter().filter_map(|map| map.as_ref()).collect();
+        result.extend(self.refl.iter());
+        result
+    }
+
+    /// Returns every texture map of the material, mutably.
+    pub fn texture_maps_mut(&mut self) -> Vec<&mut TextureMap> {
+        let Material {
+            ref mut map_ka, ref mut map_kd, ref mut map_ks, ref mut map_ns, ref mut map_d,
+            ref mut map_bump, ref mut disp, ref mut decal, ref mut map_ke, ref mut map_pr,
+            ref mut map_pm, ref mut map_ps, ref mut norm, ref mut refl, ..
+        } = *self;
+
+        let maps = vec![
+            map_ka, map_kd, map_ks, map_ns, map_d, map_bump, disp, decal, map_ke, map_pr, map_pm,
+            map_ps, norm,
+        ];
+
+        let mut result: Vec<&mut TextureMap> =
+            maps.into_iter().filter_map(|map| map.as_mut()).collect();
+        result.extend(refl.iter_mut());
+        result
+    }
+}
+
+/// An illumination model of a material. (`illum`)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IlluminationModel {
+    /// `0`: Color on and ambient off.
+    ColorOnAmbientOff,
+    /// `1`: Color on and ambient on.
+    ColorOnAmbientOn,
+    /// `2`: Highlight on.
+    HighlightOn,
+    /// `3`: Reflection on and ray trace on.
+    ReflectionRayTrace,
+    /// `4`: Transparency by glass on, and reflection by ray trace on.
+    GlassRayTrace,
+    /// `5`: Reflection by Fresnel on and ray trace on.
+    FresnelRayTrace,
+    /// `6`: Transparency by refraction on, and reflection by ray trace on with Fresnel off.
+    RefractionRayTrace,
+    /// `7`: Transparency by refraction on, and reflection by ray trace on with Fresnel on.
+    RefractionFresnelRayTrace,
+    /// `8`: Reflection on and ray trace off.
+    Reflection,
+    /// `9`: Transparency by glass on, and reflection by ray trace off.
+    Glass,
+    /// `10`: Casts shadows onto invisible surfaces.
+    ShadowsOnInvisibleSurfaces,
+    /// Any other illumination model which is not defined by the specification.
+    Other(u32),
+}
+
+impl IlluminationModel {
+    /// Converts the number of an `illum` statement into an `IlluminationModel`.
+    pub fn from_u32(illum: u32) -> Self {
+        use self::IlluminationModel::*;
+
+        match illum {
+            0 => ColorOnAmbientOff,
+            1 => ColorOnAmbientOn,
+            2 => HighlightOn,
+            3 => ReflectionRayTrace,
+            4 => GlassRayTrace,
+            5 => FresnelRayTrace,
+            6 => RefractionRayTrace,
+            7 => RefractionFresnelRayTrace,
+            8 => Reflection,
+            9 => Glass,
+            10 => ShadowsOnInvisibleSurfaces,
+            other => Other(other),
+        }
+    }
+
+    /// Returns the number which is written in the `illum` statement.
+    pub fn to_u32(&self) -> u32 {
+        use self::IlluminationModel::*;
+
+        match *self {
+            ColorOnAmbientOff => 0,
+            ColorOnAmbientOn => 1,
+            HighlightOn => 2,
+            ReflectionRayTrace => 3,
+            GlassRayTrace => 4,
+            FresnelRayTrace => 5,
+            RefractionRayTrace => 6,
+            RefractionFresnelRayTrace => 7,
+            Reflection => 8,
+            Glass => 9,
+            ShadowsOnInvisibleSurfaces => 10,
+            Other(other) => other,
+        }
+    }
+
+    /// Returns `true` if the ambient term is used.
+    pub fn has_ambient(&self) -> bool {
+        matches!(self.to_u32(), 1..=10)
+    }
+
+    /// Returns `true` if the specular highlight is used.
+    pub fn has_specular(&self) -> bool {
+        matches!(self.to_u32(), 2..=10)
+    }
+
+    /// Returns `true` if the surface reflects its surroundings.
+    pub fn has_reflection(&self) -> bool {
+        matches!(self.to_u32(), 3..=9)
+    }
+
+    /// Returns `true` if the reflection is calculated by ray tracing.
+    pub fn has_ray_trace(&self) -> bool {
+        matches!(self.to_u32(), 3..=7)
+    }
+
+    /// Returns `true` if the Fresnel effect is applied to the reflection.
+    pub fn has_fresnel(&self) -> bool {
+        matches!(self.to_u32(), 5 | 7)
+    }
+
+    /// Returns `true` if the surface is transparent, either by glass or by refraction.
+    pub fn has_transparency(&self) -> bool {
+        matches!(self.to_u32(), 4 | 6 | 7 | 9)
+    }
+
+    /// Returns `true` if light is refracted through the surface.
+    pub fn has_refraction(&self) -> bool {
+        matches!(self.to_u32(), 6 | 7)
+    }
+
+    /// Returns `true` if the surface casts shadows onto invisible surfaces.
+    pub fn casts_shadows_onto_invisible_surfaces(&self) -> bool {
+        *self == IlluminationModel::ShadowsOnInvisibleSurfaces
+    }
+}
+
+/// A color of a material, in one of the forms allowed by the specification.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorSpec {
+    /// A color in the RGB color space. (`Kd r g b`)
+    Rgb([f32; 3]),
+    /// A color in the CIEXYZ color space. (`Kd xyz x y z`)
+    Xyz([f32; 3]),
+    /// A color defined by a spectral curve file, multiplied by `factor`.
+    /// (`Kd spectral file.rfl factor`)
+    Spectral {
+        /// Path of the `.rfl` file, as written in the `.mtl` file.
+        file: String,
+        /// Multiplier of the values in the file.
+        factor: f32,
+    },
+}
+
+impl ColorSpec {
+    /// Returns the color as a linear sRGB value.
+    ///
+    /// `Xyz` colors are converted with `xyz_to_linear_srgb`. Spectral curve files are not read,
+    /// so a `Spectral` color is approximated as a gray of intensity `factor`.
+    pub fn to_rgb(&self) -> [f32; 3] {
+        match *self {
+            ColorSpec::Rgb(rgb) => rgb,
+            ColorSpec::Xyz(xyz) => xyz_to_linear_srgb(xyz),
+            ColorSpec::Spectral { factor, .. } => [factor, factor, factor],
+        }
+    }
+}
+
+/// Converts a CIEXYZ color into linear sRGB, using the D65 white point.
+pub fn xyz_to_linear_srgb(xyz: [f32; 3]) -> [f32; 3] {
+    let [x, y, z] = xyz;
+    [
+         3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+        -0.969266 * x + 1.8760108 * y + 0.041556 * z,
+         0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    ]
+}
+
+/// A texture map statement such as `map_Kd -s 2 2 1 texture.png`, with all of its options.
+///
+/// Options which were not given hold their default values defined by the specification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureMap {
+    /// Path of the texture file, as written in the `.mtl` file.
+    pub path: String,
+
+    /// Whether horizontal texture blending is enabled. (`-blendu`, default `true`)
+    pub blendu: bool,
+    /// Whether vertical texture blending is enabled. (`-blendv`, default `true`)
+    pub blendv: bool,
+    /// Bump multiplier. (`-bm`, default `1.0`)
+    pub bm: f32,
+    /// Boost of the mip-map sharpness. (`-boost`)
+    pub boost: Option<f32>,
+    /// Whether color correction is enabled. (`-cc`, default `false`)
+    pub cc: bool,
+    /// Whether clamping of the texture coordinates is enabled. (`-clamp`, default `false`)
+    pub clamp: bool,
+    /// Channel of the file which is used for scalar or bump textures. (`-imfchan`)
+    ///
+    /// If it is not given, `l` is used for bump maps and `m` for decal maps.
+    pub imfchan: Option<Channel>,
+    /// Base value and gain of the texture values. (`-mm`, default `[0.0, 1.0]`)
+    pub mm: [f32; 2],
+    /// Origin offset of the texture coordinates. (`-o`, default `[0.0, 0.0, 0.0]`)
+    pub o: [f32; 3],
+    /// Scale of the texture coordinates. (`-s`, default `[1.0, 1.0, 1.0]`)
+    pub s: [f32; 3],
+    /// Turbulence of the texture coordinates. (`-t`, default `[0.0, 0.0, 0.0]`)
+    pub t: [f32; 3],
+    /// Resolution of the texture to be created. (`-texres`)
+    pub texres: Option<u32>,
+    /// Type of the reflection map. (`-type`, only meaningful for `refl`)
+    pub ty: Option<ReflectionType>,
+}
+
+impl Default for TextureMap {
+    fn default() -> Self {
+        TextureMap {
+            path: String::new(),
+            blendu: true,
+            blendv: true,
+            bm: 1.0,
+            boost: None,
+            cc: false,
+            clamp: false,
+            imfchan: None,
+            mm: [0.0, 1.0],
+            o: [0.0, 0.0, 0.0],
+            s: [1.0, 1.0, 1.0],
+            t: [0.0, 0.0, 0.0],
+            texres: None,
+            ty: None,
+        }
+    }
+}
+
+/// A channel of a texture file. (`-imfchan`)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Channel {
+    /// Red channel.
+    R,
+    /// Green channel.
+    G,
+    /// Blue channel.
+    B,
+    /// Matte channel.
+    M,
+    /// Luminance channel.
+    L,
+    /// Z-depth channel.
+    Z,
+}
+
+/// The type of a reflection map. (`-type`)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReflectionType {
+    /// A sphere map.
+    Sphere,
+    /// The top face of a cube map.
+    CubeTop,
+    /// The bottom face of a cube map.
+    CubeBottom,
+    /// The front face of a cube map.
+    CubeFront,
+    /// The back face of a cube map.
+    CubeBack,
+    /// The left face of a cube map.
+    CubeLeft,
+    /// The right face of a cube map.
+    CubeRight,
+}
@@ -0,0 +1,222 @@
+//! Evaluates B-spline and NURBS curves and surfaces
+
+/// Returns the clamped knot vector of a Bezier curve of `degree` with `count` control points,
+/// which consists of `(count - 1) / degree` segments.
+///
+/// `parameters` are the parameter values at the ends of the segments. If it is empty, the
+/// segments are placed at `0, 1, 2, ...`. Returns `None` if the numbers do not match.
+pub fn bezier_knots(degree: usize, count: usize, parameters: &[f32]) -> Option<Vec<f32>> {
+    if degree == 0 || count < degree + 1 || !(count - 1).is_multiple_of(degree) { return None }
+    let segments = (count - 1) / degree;
+
+    let ends: Vec<f32> = match parameters.len() {
+        0 => (0..segments + 1).map(|i| i as f32).collect(),
+        len if len == segments + 1 => parameters.to_vec(),
+        _ => return None
+    };
+
+    let mut knots = Vec::with_capacity(count + degree + 1);
+    for (i, &end) in ends.iter().enumerate() {
+        let multiplicity = if i == 0 || i == segments { degree + 1 } else { degree };
+        knots.extend(std::iter::repeat_n(end, multiplicity));
+    }
+    Some(knots)
+}
+
+/// Checks that `knots` is a non-decreasing knot vector of a B-spline of `degree` with `count`
+/// control points.
+pub fn is_valid(knots: &[f32], degree: usize, count: usize) -> bool {
+    count > degree &&
+        knots.len() == count + degree + 1 &&
+        knots.windows(2).all(|pair| pair[0] <= pair[1]) &&
+        knots[degree] < knots[count]
+}
+
+/// Returns the valid parameter range of a B-spline of `degree` with `count` control points.
+pub fn domain(knots: &[f32], degree: usize, count: usize) -> [f32; 2] {
+    [knots[degree], knots[count]]
+}
+
+/// Returns the distinct knots within `range`, including both of its ends.
+pub fn breakpoints(knots: &[f32], range: [f32; 2]) -> Vec<f32> {
+    let mut points = vec![range[0]];
+    for &knot in knots {
+        if knot > *points.last().unwrap() && knot < range[1] {
+            points.push(knot);
+        }
+    }
+    points.push(range[1]);
+    points
+}
+
+/// Finds the index `i` of the knot span `[knots[i], knots[i + 1])` which contains `t`.
+///
+/// `t` is clamped into the domain, and the end of the domain belongs to the last span.
+pub fn span(knots: &[f32], degree: usize, count: usize, t: f32) -> usize {
+    if t >= knots[count] {
+        // Skip the trailing spans of zero length
+        let mut i = count - 1;
+        while i > degree && knots[i] == knots[count] { i -= 1 }
+        return i
+    }
+    if t <= knots[degree] {
+        let mut i = degree;
+        while i < count - 1 && knots[i + 1] == knots[degree] { i += 1 }
+        return i
+    }
+
+    let (mut low, mut high) = (degree, count);
+    while high - low > 1 {
+        let middle = (low + high) / 2;
+        if t < knots[middle] { high = middle } else { low = middle }
+    }
+    low
+}
+
+/// Evaluates the `degree + 1` non-zero basis functions of the span `span` at `t`, with the
+/// Cox-de Boor recursion.
+pub fn basis(knots: &[f32], degree: usize, span: usize, t: f32) -> Vec<f32> {
+    let mut values = vec![0.0; degree + 1];
+    let mut left = vec![0.0; degree + 1];
+    let mut right = vec![0.0; degree + 1];
+
+    values[0] = 1.0;
+    for j in 1..degree + 1 {
+        left[j] = t - knots[span + 1 - j];
+        right[j] = knots[span + j] - t;
+
+        let mut saved = 0.0;
+        for r in 0..j {
+            let denominator = right[r + 1] + left[j - r];
+            let temp = if denominator == 0.0 { 0.0 } else { values[r] / denominator };
+            values[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        values[j] = saved;
+    }
+    values
+}
+
+/// Evaluates a B-spline curve at `t`. Control points are given in homogeneous coordinates, i.e.
+/// each of them is `[x * w, y * w, z * w, w]`.
+pub fn curve_point(knots: &[f32], degree: usize, points: &[[f32; 4]], t: f32) -> [f32; 4] {
+    let span = span(knots, degree, points.len(), t);
+    let values = basis(knots, degree, span, t);
+
+    let mut result = [0.0; 4];
+    for (i, value) in values.iter().enumerate() {
+        let point = points[span - degree + i];
+        for k in 0..4 {
+            result[k] += value * point[k];
+        }
+    }
+    result
+}
+
+/// Evaluates a B-spline surface at `(u, v)`. Control points are given in homogeneous
+/// coordinates, in rows of `counts[0]` points along `u`.
+pub fn surface_point(knots: [&[f32]; 2], degree: [usize; 2], counts: [usize; 2],
+                     points: &[[f32; 4]], u: f32, v: f32) -> [f32; 4]
+{
+    let span_u = span(knots[0], degree[0], counts[0], u);
+    let span_v = span(knots[1], degree[1], counts[1], v);
+    let values_u = basis(knots[0], degree[0], span_u, u);
+    let values_v = basis(knots[1], degree[1], span_v, v);
+
+    let mut result = [0.0; 4];
+    for (j, value_v) in values_v.iter().enumerate() {
+        let row = (span_v - degree[1] + j) * counts[0];
+        for (i, value_u) in values_u.iter().enumerate() {
+            let point = points[row + span_u - degree[0] + i];
+            for k in 0..4 {
+                result[k] += value_u * value_v * point[k];
+            }
+        }
+    }
+    result
+}
+
+/// Returns the knot vector and the control points of the derivative of a B-spline of `degree`,
+/// which is a B-spline of `degree - 1`.
+pub fn derivative(knots: &[f32], degree: usize, points: &[[f32; 4]]) -> (Vec<f32>, Vec<[f32; 4]>) {
+    let derivative = points.windows(2).enumerate().map(|(i, pair)| {
+        let length = knots[i + degree + 1] - knots[i + 1];
+        let scale = if length == 0.0 { 0.0 } else { degree as f32 / length };
+        let mut point = [0.0; 4];
+        for k in 0..4 {
+            point[k] = scale * (pair[1][k] - pair[0][k]);
+        }
+        point
+    }).collect();
+    (knots[1..knots.len() - 1].to_vec(), derivative)
+}
+
+/// Converts a point in homogeneous coordinates into Cartesian coordinates.
+pub fn project(point: [f32; 4]) -> [f32; 3] {
+    let w = if point[3] == 0.0 { 1.0 } else { point[3] };
+    [point[0] / w, point[1] / w, point[2] / w]
+}
+
+/// Returns the distance between `a` and `b`.
+pub fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let d = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+}
+
+/// Returns the distance between `point` and the line segment from `a` to `b`.
+pub fn deviation(a: [f32; 3], b: [f32; 3], point: [f32; 3]) -> f32 {
+    let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let ap = [point[0] - a[0], point[1] - a[1], point[2] - a[2]];
+    let length = ab[0] * ab[0] + ab[1] * ab[1] + ab[2] * ab[2];
+    if length == 0.0 { return distance(a, point) }
+
+    let t = ((ap[0] * ab[0] + ap[1] * ab[1] + ap[2] * ab[2]) / length).clamp(0.0, 1.0);
+    distance([a[0] + ab[0] * t, a[1] + ab[1] * t, a[2] + ab[2] * t], point)
+}
+
+/// Returns the angle in radians between the directions from `a` to `m` and from `m` to `b`.
+pub fn angle(a: [f32; 3], m: [f32; 3], b: [f32; 3]) -> f32 {
+    let u = [m[0] - a[0], m[1] - a[1], m[2] - a[2]];
+    let v = [b[0] - m[0], b[1] - m[1], b[2] - m[2]];
+    let lengths = distance(a, m) * distance(m, b);
+    if lengths == 0.0 { return 0.0 }
+
+    ((u[0] * v[0] + u[1] * v[1] + u[2] * v[2]) / lengths).clamp(-1.0, 1.0).acos()
+}
+
+#[test]
+fn test_basis() {
+    let knots = [0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 3.0, 3.0];
+    for &t in &[0.0, 0.5, 1.0, 2.5, 3.0] {
+        let span = span(&knots, 2, 5, t);
+        assert!(knots[span] <= t && (t < knots[span + 1] || t == 3.0));
+
+        let sum: f32 = basis(&knots, 2, span, t).iter().sum();
+        assert!((sum - 1.0).abs() < 1e-6);
+    }
+
+    assert_eq!(bezier_knots(3, 7, &[]), Some(vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0,
+                                                   2.0, 2.0, 2.0, 2.0]));
+    assert_eq!(bezier_knots(3, 6, &[]), None);
+}
+
+#[test]
+fn test_curve_point() {
+    // A quadratic Bezier curve, as a B-spline
+    let knots = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+    let points = [[0.0, 0.0, 0.0, 1.0], [1.0, 2.0, 0.0, 1.0], [2.0, 0.0, 0.0, 1.0]];
+    assert_eq!(project(curve_point(&knots, 2, &points, 0.5)), [1.0, 1.0, 0.0]);
+    assert_eq!(project(curve_point(&knots, 2, &points, 1.0)), [2.0, 0.0, 0.0]);
+
+    // The derivative at the start is `degree * (P1 - P0)`
+    let (knots_1, points_1) = derivative(&knots, 2, &points);
+    assert_eq!(curve_point(&knots_1, 1, &points_1, 0.0), [2.0, 4.0, 0.0, 0.0]);
+
+    // A quarter of the unit circle, as a rational curve
+    let w = 0.5f32.sqrt();
+    let points = [[1.0, 0.0, 0.0, 1.0], [w, w, 0.0, w], [0.0, 1.0, 0.0, 1.0]];
+    for &t in &[0.25, 0.5, 0.75] {
+        let [x, y, _] = project(curve_point(&knots, 2, &points, t));
+        assert!((x * x + y * y - 1.0).abs() < 1e-5);
+    }
+}
@@ -1,404 +1,589 @@
 //! Parses `.obj` format which stores 3D mesh data
 
-use std::collections::{HashMap, VecMap};
-use std::simd::f32x4;
-use lex::lex;
-use error::{parse_error, ParseErrorKind};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::BufRead;
+use std::ops::Index;
+use crate::lex::{lex, n, index};
+use crate::freeform::{Connection, Curve, Curve2D, FreeFormAttributes, Surface};
+use crate::error::{ObjResult, ParseError};
 
 /// Parses a wavefront `.obj` format
-pub fn load_obj<T: Buffer>(mut input: T) -> Obj {
+pub fn load_obj<T: BufRead>(input: T) -> ObjResult<Obj> {
+    load_obj_with_options(input, &Default::default()).map(|(obj, _)| obj)
+}
+
+/// Parses a wavefront `.obj` format with the given options.
+///
+/// Returns the parsed `Obj` along with the statements which were skipped in
+/// `Strictness::Lenient` mode. The list of warnings is always empty in `Strictness::Strict` mode.
+pub fn load_obj_with_options<T: BufRead>(mut input: T, options: &LoadOptions)
+    -> ObjResult<(Obj, Vec<ParseError>)>
+{
+    let mut warnings = Vec::new();
+
     let mut name = String::new();
     let mut material_libraries = Vec::new();
+    let mut calls = Vec::new();
 
     let mut vertices = Vec::new();
     let mut tex_coords = Vec::new();
     let mut normals = Vec::new();
     let mut param_vertices = Vec::new();
 
-    let points = Vec::new();
-    let lines = Vec::new();
+    let mut points = Vec::new();
+    let mut lines = Vec::new();
     let mut polygons = Vec::new();
-
-    let counter = Counter::new(&points, &lines, &polygons);
-    let mut group_builder       = counter.hash_map("default".to_string());
-    let mut mesh_builder        = counter.hash_map(String::new());
-    let mut smoothing_builder   = counter.vec_map();
-    let mut merging_builder     = counter.vec_map();
-
-    lex(&mut input, |stmt, args| {
+    let mut source_lines = SourceLines::default();
+
+    let mut attributes = FreeFormAttributes::default();
+    let mut curves = Vec::new();
+    let mut curves_2d = Vec::new();
+    let mut surfaces = Vec::new();
+    let mut connections = Vec::new();
+    let mut body = None;
+
+    let mut group_builder       = GroupBuilder::group_map(Some("default".to_string()));
+    let mut mesh_builder        = GroupBuilder::group_map(Some(String::new()));
+    let mut smoothing_builder   = GroupBuilder::btree_map();
+    let mut merging_builder     = GroupBuilder::btree_map();
+    let mut object_builder      = GroupBuilder::group_map(None);
+
+    let maybe_warnings = match options.strictness {
+        Strictness::Strict => None,
+        Strictness::Lenient => Some(&mut warnings),
+    };
+
+    lex(&mut input, maybe_warnings, |stmt, args, line| {
         macro_rules! f {
-            ($args:ident) => ({ &$args.iter().map(|&input| n(input)).collect::<Vec<f32>>()[] })
+            ($args:ident) => ({
+                &$args.iter().map(|&input| n(input)).collect::<Result<Vec<f32>, _>>()?[..]
+            })
         }
         macro_rules! s {
-            ($param:ident) => { &$param.split('/').collect::<Vec<&str>>()[] }
+            ($param:ident) => { &$param.split('/').collect::<Vec<&str>>()[..] }
+        }
+        macro_rules! m {
+            { $ty:ident, $first:ident, $rest:ident; $($name:ident $pat:pat => $exp:expr)* } => (
+                // First, detect the type of the vertices with the first argument
+                // Then apply it to the rest of the arguments
+                match *s!($first) {
+                    $(
+                        $pat => $ty::$name({
+                            let mut element = vec![ $exp ];
+                            for param in $rest {
+                                match *s!(param) {
+                                    $pat => element.push($exp),
+                                    _ => error!(InconsistentVertexFormat, param)
+                                }
+                            }
+                            element
+                        }),
+                    )*
+                    _ => error!(WrongTypeOfArguments, $first)
+                }
+            )
+        }
+        macro_rules! counter {
+            () => (Counter::new(&points, &lines, &polygons))
         }
 
         match stmt {
+            // General statement
+            "call" => match args {
+                [] => error!(WrongNumberOfArguments),
+                _ => calls.push(Call {
+                    path: args[0].to_string(),
+                    args: args[1..].iter().map(|arg| arg.to_string()).collect()
+                })
+            },
+
             // Vertex data
-            "v" => vertices.push(match f!(args) {
-                [x, y, z, w] => f32x4(x, y, z, w),
-                [x, y, z] => f32x4(x, y, z, 1.0),
+            "v" => vertices.push(match *f!(args) {
+                [x, y, z, w] => [x, y, z, w],
+                [x, y, z] => [x, y, z, 1.0],
                 _ => error!(WrongNumberOfArguments)
             }),
-            "vt" => tex_coords.push(match f!(args) {
-                [u, v, w] => f32x4(u, v, w, 0.0),
-                [u, v] => f32x4(u, v, 0.0, 0.0),
-                [u] => f32x4(u, 0.0, 0.0, 0.0),
+            "vt" => tex_coords.push(match *f!(args) {
+                [u, v, w] => [u, v, w, 0.0],
+                [u, v] => [u, v, 0.0, 0.0],
+                [u] => [u, 0.0, 0.0, 0.0],
                 _ => error!(WrongNumberOfArguments)
             }),
-            "vn" => normals.push(match f!(args) {
-                [x, y, z] => f32x4(x, y, z, 0.0),
+            "vn" => normals.push(match *f!(args) {
+                [x, y, z] => [x, y, z, 0.0],
                 _ => error!(WrongNumberOfArguments)
             }),
-            "vp" => param_vertices.push(match f!(args) {
-                [u, v, w] => f32x4(u, v, w, 0.0),
-                [u, v] => f32x4(u, v, 1.0, 0.0),
-                [u] => f32x4(u, 0.0, 1.0, 0.0),
+            "vp" => param_vertices.push(match *f!(args) {
+                [u, v, w] => [u, v, w, 0.0],
+                [u, v] => [u, v, 1.0, 0.0],
+                [u] => [u, 0.0, 1.0, 0.0],
                 _ => error!(WrongNumberOfArguments)
             }),
 
             // Free-form curve / surface attributes
-            "cstype" => {
-                let _rational: bool;
-                let geometry = match args {
-                    ["rat", ty] => {
-                        _rational = true;
-                        ty
-                    }
-                    [ty] => {
-                        _rational = false;
-                        ty
-                    }
-                    _ => unimplemented!()
-                };
-
-                match geometry {
-                    "bmatrix" => unimplemented!(),
-                    "bezier" => unimplemented!(),
-                    "bspline" => unimplemented!(),
-                    "cardinal" => unimplemented!(),
-                    "taylor" => unimplemented!(),
-                    _ => unimplemented!()
-                }
-            }
-            "deg" => match f!(args) {
-                [_deg_u, _deg_v]  => unimplemented!(),
-                [_deg_u] => unimplemented!(),
-                _ => unimplemented!(),
-            },
-            "bmat" => unimplemented!(),
-            "step" => unimplemented!(),
+            "cstype" | "deg" | "bmat" | "step" => attributes.parse(stmt, args)?,
 
             // Elements
-            "p" => unimplemented!(),
-            "l" => unimplemented!(),
+            "p" => {
+                if args.is_empty() { error!(WrongNumberOfArguments) }
+
+                let new: Vec<Point> = args.iter()
+                    .map(|param| index(param, vertices.len()))
+                    .collect::<Result<_, _>>()?;
+                source_lines.points.extend(new.iter().map(|_| line));
+                points.extend(new);
+            }
+            "l" => {
+                if args.len() < 2 { error!(WrongNumberOfArguments) }
+                let (first, rest) = args.split_first().unwrap();
+
+                let (nv, nt) = (vertices.len(), tex_coords.len());
+
+                lines.push(m! { Line, first, rest;
+                    P   [p]        => index(p, nv)?
+                    PT  [p, t]     => (index(p, nv)?, index(t, nt)?)
+                });
+                source_lines.lines.push(line);
+            }
             "f" => {
-                if args.len() < 3 { unimplemented!() }
-                let mut args = args.iter();
-                let first = args.next().unwrap();
-
-                macro_rules! m {
-                    { $($name:ident $pat:pat => $exp:expr)* } => (
-                        // First, detect the type of the vertices with the first argument
-                        // Then apply it to the rest of the arguments
-                        match s!(first) {
-                            $(
-                                $pat => Polygon::$name({
-                                    let mut polygon = vec![ $exp ];
-                                    for param in args {
-                                        match s!(param) {
-                                            $pat => polygon.push($exp),
-                                            _ => unimplemented!()
-                                        }
-                                    }
-                                    polygon
-                                }),
-                            )*
-                            _ => unimplemented!()
-                        }
-                    )
+                if args.len() < 3 { error!(WrongNumberOfArguments) }
+                let counts = [vertices.len(), tex_coords.len(), normals.len()];
+
+                let mut corners = Vec::with_capacity(args.len());
+                for param in args {
+                    corners.push(corner(param, counts)?);
                 }
 
-                polygons.push(m! {
-                    P   [p]        => (n(p))
-                    PT  [p, t]     => (n(p), n(t))
-                    PN  [p, "", u] => (n(p), n(u))
-                    PTN [p, t, u]  => (n(p), n(t), n(u))
-                });
+                polygons.push(polygon(&corners, args, options.mixed_formats)?);
+                source_lines.polygons.push(line);
+            }
+            // A failed element leaves no body, so that its body statements are not applied to the
+            // previous element
+            "curv" => {
+                body = None;
+                curves.push(Curve::parse(args, &attributes, vertices.len())?);
+                body = Some(Body::Curve);
+            }
+            "curv2" => {
+                body = None;
+                curves_2d.push(Curve2D::parse(args, &attributes, param_vertices.len())?);
+                body = Some(Body::Curve2D);
+            }
+            "surf" => {
+                body = None;
+                let counts = [vertices.len(), tex_coords.len(), normals.len()];
+                surfaces.push(Surface::parse(args, &attributes, counts, options.mixed_formats)?);
+                body = Some(Body::Surface);
             }
-            "curv" => unimplemented!(),
-            "curv2" => unimplemented!(),
-            "surf" => unimplemented!(),
 
             // Free-form curve / surface body statements
-            "parm" => unimplemented!(),
-            "trim" => unimplemented!(),
-            "hole" => unimplemented!(),
-            "scrv" => unimplemented!(),
-            "sp" => unimplemented!(),
-            "end" => unimplemented!(),
+            "parm" | "trim" | "hole" | "scrv" | "sp" => {
+                let counts = [param_vertices.len(), curves_2d.len()];
+                match body {
+                    Some(Body::Curve) => curves.last_mut().unwrap().parse_body(stmt, args, counts)?,
+                    Some(Body::Curve2D) => {
+                        curves_2d.last_mut().unwrap().parse_body(stmt, args, counts)?
+                    }
+                    Some(Body::Surface) => {
+                        surfaces.last_mut().unwrap().parse_body(stmt, args, counts)?
+                    }
+                    None => error!(UnexpectedStatement)
+                }
+            }
+            "end" => match body {
+                Some(_) => body = None,
+                None => error!(UnexpectedStatement)
+            },
 
             // Connectivity between free-form surfaces
-            "con" => unimplemented!(),
+            "con" => connections.push(Connection::parse(args, [surfaces.len(), curves_2d.len()])?),
 
             // Grouping
             "g" => match args {
-                [name] => group_builder.start(name.to_string()),
-                _ => unimplemented!()
+                [] => group_builder.start("default".to_string(), counter!()),
+                names => {
+                    let names = names.iter().map(|name| name.to_string()).collect();
+                    group_builder.start_all(names, counter!())
+                }
             },
             "s" => match args {
-                ["off"] | ["0"] => smoothing_builder.end(),
-                [param] => smoothing_builder.start(n(param)),
+                ["off"] | ["0"] => smoothing_builder.end(counter!()),
+                [param] => smoothing_builder.start(n(param)?, counter!()),
                 _ => error!(WrongNumberOfArguments)
             },
             "mg" => match args {
-                ["off"] | ["0"] => merging_builder.end(),
-                [param] => merging_builder.start(n(param)),
+                ["off"] | ["0"] => merging_builder.end(counter!()),
+                [param] => merging_builder.start(n(param)?, counter!()),
                 _ => error!(WrongNumberOfArguments)
             },
             "o" => {
-                if !name.is_empty() { unimplemented!() }
+                let object = args.join(" ");
+                if object_builder.result.is_empty() {
+                    name = object.clone();
+                }
 
-                name = args.connect(" ");
+                object_builder.start(object, counter!());
             }
 
             // Display / render attributes
-            "bevel" => unimplemented!(),
-            "c_interp" => unimplemented!(),
-            "d_interp" => unimplemented!(),
-            "lod" => unimplemented!(),
+            "bevel" => error!(UnsupportedStatement),
+            "c_interp" => error!(UnsupportedStatement),
+            "d_interp" => error!(UnsupportedStatement),
+            "lod" => error!(UnsupportedStatement),
             "usemtl" => match args {
-                [material] => mesh_builder.start(material.to_string()),
+                [material] => mesh_builder.start(material.to_string(), counter!()),
                 _ => error!(WrongNumberOfArguments)
             },
             "mtllib" => {
-                let paths: Vec<String> = args.iter().map(|path| path.to_string()).collect();
-                material_libraries.push_all(&paths[]);
+                material_libraries.extend(args.iter().map(|path| path.to_string()));
             }
-            "shadow_obj" => unimplemented!(),
-            "trace_obj" => unimplemented!(),
-            "ctech" => unimplemented!(),
-            "stech" => unimplemented!(),
+            "shadow_obj" => error!(UnsupportedStatement),
+            "trace_obj" => error!(UnsupportedStatement),
+            "ctech" | "stech" => attributes.parse(stmt, args)?,
 
             // Unexpected statement
             _ => error!(UnexpectedStatement)
         }
 
-        fn n<T: ::std::str::FromStr>(input: &str) -> T {
-            match input.parse() {
-                Ok(number) => number,
-                Err(_)=> unimplemented!()
-            }
-        }
+        Ok(())
+    })?;
 
-        None
-    });
+    let counter = Counter::new(&points, &lines, &polygons);
+    group_builder.end(counter);
+    mesh_builder.end(counter);
+    smoothing_builder.end(counter);
+    merging_builder.end(counter);
+    object_builder.end(counter);
+
+    let mut obj = Obj {
+        name,
+        material_libraries,
+        calls,
+
+        vertices,
+        tex_coords,
+        normals,
+        param_vertices,
+
+        points,
+        lines,
+        polygons,
+        zero_based: false,
+        source_lines,
+
+        curves,
+        curves_2d,
+        surfaces,
+        connections,
 
-    group_builder.end();
-    mesh_builder.end();
-    smoothing_builder.end();
-    merging_builder.end();
+        groups: group_builder.result,
+        meshes: mesh_builder.result,
+        smoothing_groups: smoothing_builder.result,
+        merging_groups: merging_builder.result,
+        objects: object_builder.result,
+    };
 
-    Obj {
-        name: name,
-        material_libraries: material_libraries,
+    if options.zero_based {
+        obj.rebase_to_zero();
+    }
 
-        vertices: vertices,
-        tex_coords: tex_coords,
-        normals: normals,
-        param_vertices: param_vertices,
+    Ok((obj, warnings))
+}
 
-        points: points,
-        lines: lines,
-        polygons: polygons,
+/// Kind of the free-form element whose body statements are being parsed.
+#[derive(Clone, Copy)]
+enum Body {
+    Curve,
+    Curve2D,
+    Surface,
+}
 
-        groups: group_builder.result,
-        meshes: mesh_builder.result,
-        smoothing_groups: smoothing_builder.result,
-        merging_groups: merging_builder.result
+/// A vertex of a face, which is a position index with optional texture coordinate and normal
+/// indices.
+pub(crate) type Corner = (u32, Option<u32>, Option<u32>);
+
+/// Parses a vertex of a face such as `1/2/3`. `counts` are the numbers of the vertices, texture
+/// coordinates and normals which have been defined so far.
+pub(crate) fn corner(param: &str, counts: [usize; 3]) -> Result<Corner, ParseError> {
+    let [nv, nt, nn] = counts;
+    Ok(match *param.split('/').collect::<Vec<&str>>() {
+        [p]        => (index(p, nv)?, None, None),
+        [p, t]     => (index(p, nv)?, Some(index(t, nt)?), None),
+        [p, "", u] => (index(p, nv)?, None, Some(index(u, nn)?)),
+        [p, t, u]  => (index(p, nv)?, Some(index(t, nt)?), Some(index(u, nn)?)),
+        _ => error!(WrongTypeOfArguments, param)
+    })
+}
+
+/// Constructs a polygon from the vertices of an `f` statement. `args` are the arguments of the
+/// statement, which are used to locate an inconsistent vertex.
+pub(crate) fn polygon(corners: &[Corner], args: &[&str], mixed: MixedFormats)
+    -> Result<Polygon, ParseError>
+{
+    let format = |&(_, t, u): &Corner| (t.is_some(), u.is_some());
+
+    let first = format(&corners[0]);
+    let (has_t, has_u) = match corners.iter().position(|corner| format(corner) != first) {
+        None => first,
+        Some(i) => match mixed {
+            MixedFormats::Error => error!(InconsistentVertexFormat, args[i]),
+            MixedFormats::Degrade => (corners.iter().all(|&(_, t, _)| t.is_some()),
+                                      corners.iter().all(|&(_, _, u)| u.is_some())),
+            MixedFormats::Fill => (corners.iter().any(|&(_, t, _)| t.is_some()),
+                                   corners.iter().any(|&(_, _, u)| u.is_some())),
+        }
+    };
+
+    let or_missing = |index: Option<u32>| index.unwrap_or(MISSING_INDEX);
+    Ok(match (has_t, has_u) {
+        (false, false) => Polygon::P(corners.iter().map(|&(p, _, _)| p).collect()),
+        (true, false) => Polygon::PT(corners.iter().map(|&(p, t, _)| (p, or_missing(t))).collect()),
+        (false, true) => Polygon::PN(corners.iter().map(|&(p, _, u)| (p, or_missing(u))).collect()),
+        (true, true) => Polygon::PTN(corners.iter()
+            .map(|&(p, t, u)| (p, or_missing(t), or_missing(u)))
+            .collect()),
+    })
+}
+
+
+/// Options for `load_obj_with_options`.
+#[derive(Clone, Debug)]
+pub struct LoadOptions {
+    /// How to treat unknown or malformed statements.
+    pub strictness: Strictness,
+    /// Whether to store the indices of elements starting from 0 instead of 1, so that they can be
+    /// used to index `Obj::vertices` and the others directly.
+    pub zero_based: bool,
+    /// How to treat faces whose vertices are written in different formats, such as `f 1/1 2 3/3`.
+    pub mixed_formats: MixedFormats,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            strictness: Strictness::Strict,
+            zero_based: false,
+            mixed_formats: MixedFormats::Error
+        }
     }
 }
 
+/// Strictness of the parser.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Strictness {
+    /// Fails on the first unknown or malformed statement.
+    Strict,
+    /// Skips unknown or malformed statements, and reports each of them as a warning.
+    Lenient,
+}
+
+/// Policy for faces whose vertices are written in different formats.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MixedFormats {
+    /// Fails with `ParseErrorKind::InconsistentVertexFormat`.
+    Error,
+    /// Drops the attributes which are missing from some of the vertices, so that
+    /// `f 1/1 2 3/3` becomes `f 1 2 3`.
+    Degrade,
+    /// Keeps the attributes which are present in some of the vertices, and fills the others with
+    /// `MISSING_INDEX`, so that `f 1/1 2 3/3` becomes `f 1/1 2/MISSING_INDEX 3/3`.
+    Fill,
+}
 
-/// Counts current total count of parsed `points`, `lines` and `polygons`.
+/// Index of an attribute which is missing from a vertex, used by `MixedFormats::Fill`.
+pub const MISSING_INDEX: u32 = u32::MAX;
+
+
+/// Current total count of parsed `points`, `lines` and `polygons`.
+#[derive(Clone, Copy)]
 struct Counter {
-    points:     *const Vec<Point>,
-    lines:      *const Vec<Line>,
-    polygons:   *const Vec<Polygon>,
+    points:     usize,
+    lines:      usize,
+    polygons:   usize,
 }
 
 impl Counter {
     /// Constructs a new `Counter`.
-    fn new(points: *const Vec<Point>, lines: *const Vec<Line>, polygons: *const Vec<Polygon>) -> Self {
+    fn new(points: &[Point], lines: &[Line], polygons: &[Polygon]) -> Self {
         Counter {
-            points:     points,
-            lines:      lines,
-            polygons:   polygons
+            points:     points.len(),
+            lines:      lines.len(),
+            polygons:   polygons.len()
         }
     }
+}
 
-    /// Returns a current count of parsed `(points, lines, polygons)`.
-    fn get(&self) -> (usize, usize, usize) {
-        unsafe { ((*self.points).len(), (*self.lines).len(), (*self.polygons).len()) }
-    }
-
-    /// Creates a `HashMap<String, Group>` builder which references `self` as counter.
-    fn hash_map<'a>(&'a self, input: String) -> GroupBuilder<'a, HashMap<String, Group>, String> {
-        let mut init = Vec::with_capacity(1);
-        init.start(0);
 
-        let mut result = HashMap::with_capacity(1);
-        result.insert(input.clone(), Group {
-            points:     init.clone(),
-            lines:      init.clone(),
-            polygons:   init
-        });
+/// Helper for creating `groups`, `meshes`, `smoothing_groups` and `merging_groups` member of
+/// `Obj`.
+struct GroupBuilder<T, K> {
+    current: Vec<K>,    // Groups which have been started and not ended yet
+    result: T
+}
 
-        GroupBuilder {
-            counter: self,
-            current: Some(input),
-            result: result
+impl GroupBuilder<GroupMap, String> {
+    /// Creates a `GroupMap` builder. If `input` is given, the group is started at the beginning.
+    fn group_map(input: Option<String>) -> Self {
+        let mut builder = GroupBuilder {
+            current: Vec::with_capacity(1),
+            result: GroupMap::new()
+        };
+        if let Some(input) = input {
+            builder.start(input, Counter { points: 0, lines: 0, polygons: 0 });
         }
+        builder
     }
+}
 
-    /// Creates a `VecMap<Group>` builder which references `self` as counter.
-    fn vec_map<'a>(&'a self) -> GroupBuilder<'a, VecMap<Group>, usize> {
+impl GroupBuilder<BTreeMap<usize, Group>, usize> {
+    /// Creates a `BTreeMap<usize, Group>` builder.
+    fn btree_map() -> Self {
         GroupBuilder {
-            counter: self,
-            current: None,
-            result: VecMap::new()
+            current: Vec::new(),
+            result: BTreeMap::new()
         }
     }
 }
 
-
-/// Helper for creating `groups`, `meshes`, `smoothing_groups` and `merging_groups` member of
-/// `Obj`.
-struct GroupBuilder<'a, T, K> {
-    counter: &'a Counter,
-    current: Option<K>, // Some(K) if some group has been started
-                        // None    otherwise
-    result: T
-}
-
-impl<'a, T, K> GroupBuilder<'a, T, K> where
+impl<T, K> GroupBuilder<T, K> where
     T: Map<K, Group>,
-    K: Clone + Key
+    K: Clone + Eq
 {
     /// Starts a group whose name is `input`.
-    fn start(&mut self, input: K) {
-        let (points, lines, polygons) = self.counter.get();
-
-        match self.current {
-            Some(ref current) if *current != input => {
-                let is_empty = {
-                    let old = &mut self.result[*current];
-                    old.points  .end(points);
-                    old.lines   .end(lines);
-                    old.polygons.end(polygons);
-
-                    old.points.is_empty() && old.lines.is_empty() && old.polygons.is_empty()
-                };
-
-                if is_empty {
-                    let result = self.result.remove(current);
-                    assert!(result.is_some());
-                }
+    fn start(&mut self, input: K, counter: Counter) {
+        self.start_all(vec![input], counter)
+    }
+
+    /// Starts the groups whose names are `inputs`, so that the following elements belong to all
+    /// of them. Groups which are current but not in `inputs` are ended.
+    fn start_all(&mut self, inputs: Vec<K>, counter: Counter) {
+        let mut unique = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            if !unique.contains(&input) { unique.push(input) }
+        }
+        let inputs = unique;
+
+        for current in self.current.iter() {
+            if inputs.contains(current) { continue }
+
+            if Self::end_range(&mut self.result, current, counter) {
+                let result = self.result.remove(current);
+                assert!(result.is_some());
             }
-            Some(_) => return,
-            None => ()
         }
 
-        let mut group = Group::new();
-        group.points   .start(points);
-        group.lines    .start(lines);
-        group.polygons .start(polygons);
+        for input in inputs.iter() {
+            if self.current.contains(input) { continue }
+
+            // A group may consist of multiple ranges, if its name appears several times
+            if let Some(group) = self.result.get_mut(input) {
+                group.points   .start(counter.points);
+                group.lines    .start(counter.lines);
+                group.polygons .start(counter.polygons);
+                continue
+            }
 
-        self.current = Some(input.clone());
+            let mut group = Group::new();
+            group.points   .start(counter.points);
+            group.lines    .start(counter.lines);
+            group.polygons .start(counter.polygons);
 
-        let result = self.result.insert(input, group);
-        assert!(result.is_none());
+            let result = self.result.insert(input.clone(), group);
+            assert!(result.is_none());
+        }
+
+        self.current = inputs;
     }
 
-    /// Ends a current group.
-    fn end(&mut self) {
-        match self.current {
-            Some(ref current) => {
-                let (points, lines, polygons) = self.counter.get();
-                let old = &mut self.result[*current];
-                old.points  .end(points);
-                old.lines   .end(lines);
-                old.polygons.end(polygons);
-            }
-            None => return
+    /// Ends current groups.
+    fn end(&mut self, counter: Counter) {
+        for current in self.current.iter() {
+            Self::end_range(&mut self.result, current, counter);
         }
+        self.current.clear();
+    }
 
-        self.current = None;
+    /// Ends the last range of the group `key`. Returns `true` if the group has become empty.
+    fn end_range(result: &mut T, key: &K, counter: Counter) -> bool {
+        let old = result.get_mut(key).unwrap();
+        old.points  .end(counter.points);
+        old.lines   .end(counter.lines);
+        old.polygons.end(counter.polygons);
+
+        old.points.is_empty() && old.lines.is_empty() && old.polygons.is_empty()
     }
 }
 
 
-/// Custom trait to interface `HashMap` and `VecMap`.
-trait Map<K: Key, V: ?Sized> : ::std::ops::IndexMut<K, Output=V> {
+/// Custom trait to interface `BTreeMap` and `GroupMap`.
+trait Map<K, V> {
     /// Interface of `insert` function.
-    fn insert(&mut self, K, V) -> Option<V>;
+    fn insert(&mut self, k: K, v: V) -> Option<V>;
     /// Interface of `get_mut` function.
     fn get_mut(&mut self, k: &K) -> Option<&mut V>;
     /// Interface of `remove` function.
     fn remove(&mut self, k: &K) -> Option<V>;
 }
 
-impl<V> Map<String, V> for HashMap<String, V> {
-    fn insert(&mut self, k: String, v: V) -> Option<V> {
+impl<V> Map<usize, V> for BTreeMap<usize, V> {
+    fn insert(&mut self, k: usize, v: V) -> Option<V> {
         self.insert(k, v)
     }
 
-    fn get_mut(&mut self, k: &String) -> Option<&mut V> {
+    fn get_mut(&mut self, k: &usize) -> Option<&mut V> {
         self.get_mut(k)
     }
 
-    fn remove(&mut self, k: &String) -> Option<V> {
+    fn remove(&mut self, k: &usize) -> Option<V> {
         self.remove(k)
     }
 }
 
-impl<V> Map<usize, V> for VecMap<V> {
-    fn insert(&mut self, k: usize, v: V) -> Option<V> {
-        self.insert(k, v)
+impl Map<String, Group> for GroupMap {
+    fn insert(&mut self, k: String, v: Group) -> Option<Group> {
+        match self.indices.get(&k) {
+            Some(&index) => Some(std::mem::replace(&mut self.entries[index].1, v)),
+            None => {
+                self.indices.insert(k.clone(), self.entries.len());
+                self.entries.push((k, v));
+                None
+            }
+        }
     }
 
-    fn get_mut(&mut self, k: &usize) -> Option<&mut V> {
-        self.get_mut(k)
+    fn get_mut(&mut self, k: &String) -> Option<&mut Group> {
+        match self.indices.get(k) {
+            Some(&index) => Some(&mut self.entries[index].1),
+            None => None
+        }
     }
 
-    fn remove(&mut self, k: &usize) -> Option<V> {
-        self.remove(k)
+    fn remove(&mut self, k: &String) -> Option<Group> {
+        let index = self.indices.remove(k)?;
+        for other in self.indices.values_mut() {
+            if *other > index { *other -= 1 }
+        }
+        Some(self.entries.remove(index).1)
     }
 }
 
-/// A trait which should be implemented by a type passed into `Key` of `Map`.
-trait Key : Eq {}
-
-impl Key for String {}
-impl Key for usize {}
-
 
 /// Custom trait for `Vec<Range>`.
 trait RangeVec {
     /// Starts new range
-    fn start(&mut self, usize);
+    fn start(&mut self, start: usize);
 
     /// Tie up the loose end of the `Vec<Range>`
-    fn end(&mut self, usize);
+    fn end(&mut self, end: usize);
 }
 
 /// Constant which is used to represent undefined bound of range.
-static UNDEFINED: usize = ::std::usize::MAX;
+const UNDEFINED: usize = usize::MAX;
 
 impl RangeVec for Vec<Range> {
     fn start(&mut self, start: usize) {
         self.push(Range {
-            start: start,
+            start,
             end: UNDEFINED
         })
     }
@@ -421,43 +606,469 @@ pub struct Obj {
     pub name: String,
     /// `.mtl` files which required by this object.
     pub material_libraries: Vec<String>,
+    /// Other files which are included by `call` statements.
+    pub calls: Vec<Call>,
 
     /// Position vectors of each vertex.
-    pub vertices: Vec<f32x4>,
+    pub vertices: Vec<[f32; 4]>,
     /// Texture coordinates of each vertex.
-    pub tex_coords: Vec<f32x4>,
+    pub tex_coords: Vec<[f32; 4]>,
     /// Normal vectors of each vertex.
-    pub normals: Vec<f32x4>,
+    pub normals: Vec<[f32; 4]>,
     /// Parametric vertices.
-    pub param_vertices: Vec<f32x4>,
+    pub param_vertices: Vec<[f32; 4]>,
 
-    /// Points which stores the index data of position vectors.
+    /// Points which store the index data of position vectors, starting from 1.
     pub points: Vec<Point>,
-    /// Lines which store the index data of vectors.
+    /// Lines which store the index data of vectors, starting from 1.
     pub lines: Vec<Line>,
-    /// Polygons which store the index data of vectors.
+    /// Polygons which store the index data of vectors, starting from 1.
+    ///
+    /// Relative indices such as `-1` are resolved into absolute ones while parsing.
     pub polygons: Vec<Polygon>,
-
-    /// Groups of multiple geometries.
-    pub groups: HashMap<String, Group>,
-    /// Geometries which consist in a same material.
-    pub meshes: HashMap<String, Group>,
+    /// Whether the indices of the elements start from 0 instead of 1.
+    pub zero_based: bool,
+    /// Line numbers of the statements which defined each point, line and polygon.
+    pub source_lines: SourceLines,
+
+    /// Free-form curves in 3D space.
+    pub curves: Vec<Curve>,
+    /// Free-form curves in the parameter space of surfaces, which are used to trim them.
+    pub curves_2d: Vec<Curve2D>,
+    /// Free-form surfaces.
+    pub surfaces: Vec<Surface>,
+    /// Connectivity between the edges of free-form surfaces.
+    pub connections: Vec<Connection>,
+
+    /// Groups of multiple geometries, in the order of their first appearance.
+    pub groups: GroupMap,
+    /// Geometries which consist in a same material, in the order of the first `usemtl` statement
+    /// of each material.
+    ///
+    /// Elements which precede every `usemtl` statement belong to the mesh named `""`, which has
+    /// no material. `Obj::material_meshes` skips it.
+    pub meshes: GroupMap,
     /// Smoothing groups.
-    pub smoothing_groups: VecMap<Group>,
+    pub smoothing_groups: BTreeMap<usize, Group>,
     /// Merging groups.
-    pub merging_groups: VecMap<Group>
+    pub merging_groups: BTreeMap<usize, Group>,
+    /// Objects which are started by `o` statements, in the order of their first appearance.
+    ///
+    /// Elements which precede every `o` statement do not belong to any object.
+    pub objects: GroupMap,
 }
 
-/// The `Point` type which stores the index of the position vector.
-pub type Point = usize;
+impl Obj {
+    /// Converts the indices of the elements to start from 0. Parsed indices are at least 1, since
+    /// `lex::index` rejects `0`, so none of them underflows.
+    fn rebase_to_zero(&mut self) {
+        if self.zero_based { return }
+
+        for point in self.points.iter_mut() {
+            *point -= 1;
+        }
+        for line in self.lines.iter_mut() {
+            match line {
+                Line::P(vertices) => for p in vertices {
+                    *p -= 1;
+                },
+                Line::PT(vertices) => for (p, t) in vertices {
+                    *p -= 1;
+                    *t -= 1;
+                },
+            }
+        }
+        for polygon in self.polygons.iter_mut() {
+            polygon.rebase_to_zero();
+        }
+
+        let decrement = |indices: &mut Vec<u32>| for i in indices { *i -= 1 };
+        for curve in self.curves.iter_mut() {
+            decrement(&mut curve.control_points);
+            decrement(&mut curve.special_points);
+        }
+        for curve in self.curves_2d.iter_mut() {
+            decrement(&mut curve.control_points);
+            decrement(&mut curve.special_points);
+        }
+        for surface in self.surfaces.iter_mut() {
+            surface.control_points.rebase_to_zero();
+            decrement(&mut surface.special_points);
+
+            let loops = surface.trims.iter_mut()
+                .chain(surface.holes.iter_mut())
+                .chain(surface.special_curves.iter_mut());
+            for segment in loops.flatten() {
+                segment.curve -= 1;
+            }
+        }
+        for connection in self.connections.iter_mut() {
+            for k in 0..2 {
+                connection.surfaces[k] -= 1;
+                connection.curves[k].curve -= 1;
+            }
+        }
 
-/// The `Line` type.
+        self.zero_based = true;
+    }
+
+    /// Returns the first index of the elements, which is `0` if `zero_based` is set or `1`
+    /// otherwise.
+    pub fn index_base(&self) -> usize {
+        if self.zero_based { 0 } else { 1 }
+    }
+
+    /// Converts `index`, which is stored in an element of this object, into a position in the
+    /// referenced list. Returns `None` if it is below `index_base`.
+    pub fn position(&self, index: usize) -> Option<usize> {
+        index.checked_sub(self.index_base())
+    }
+
+    /// Iterates over the meshes which have a material, i.e. every mesh but the one named `""`,
+    /// along with the names of their materials, in the order of `meshes`.
+    pub fn material_meshes(&self) -> impl Iterator<Item = (&str, &Group)> {
+        self.meshes.iter().filter(|(name, _)| !name.is_empty())
+    }
+
+    /// Returns the names of the groups which contain the polygon `index`, in the order of `groups`.
+    pub fn groups_of_polygon(&self, index: usize) -> Vec<&str> {
+        self.groups.iter()
+            .filter(|&(_, group)| group.polygons.iter().any(|range| range.contains(index)))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Builds an index of the material, groups and object which contain each point, line and
+    /// polygon, so that each lookup is a single binary search.
+    ///
+    /// The index borrows the object, so it has to be built again after the object is modified.
+    pub fn attribute_index(&self) -> AttributeIndex<'_> {
+        AttributeIndex {
+            obj: self,
+            elements: [
+                Intervals::new(self, |group| &group.points),
+                Intervals::new(self, |group| &group.lines),
+                Intervals::new(self, |group| &group.polygons),
+            ],
+        }
+    }
+
+    /// Iterates over the polygons which use the material `name`, in order.
+    pub fn polygons_in_material<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Polygon> {
+        self.polygons_in(self.meshes.get(name))
+    }
+
+    /// Iterates over the polygons which belong to the group `name`, in order.
+    pub fn polygons_in_group<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Polygon> {
+        self.polygons_in(self.groups.get(name))
+    }
+
+    /// Iterates over the polygons which belong to the object `name`, in order.
+    pub fn polygons_in_object<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Polygon> {
+        self.polygons_in(self.objects.get(name))
+    }
+
+    /// Iterates over the polygons within the ranges of `group`.
+    fn polygons_in<'a>(&'a self, group: Option<&'a Group>) -> impl Iterator<Item = &'a Polygon> {
+        let ranges = group.map(|group| &group.polygons[..]).unwrap_or(&[]);
+        ranges.iter().flat_map(move |range| &self.polygons[range.start..range.end])
+    }
+
+    /// Checks that every point, line and polygon references existing vectors.
+    ///
+    /// Returns every reference which is out of range, in the order of the elements. An empty list
+    /// means that the object can be rendered safely. Attributes which are marked as
+    /// `MISSING_INDEX` are not reported.
+    ///
+    /// References to `0` are not reported here, since they fail to parse in the first place.
+    pub fn validate(&self) -> Vec<InvalidIndex> {
+        let mut errors = Vec::new();
+
+        let counts = [self.vertices.len(), self.tex_coords.len(), self.normals.len()];
+        let mut check = |element, number, lines: &[usize], attribute: Attribute, index: usize| {
+            if index == MISSING_INDEX as usize { return }
+            if self.position(index).is_none_or(|i| i >= counts[attribute as usize]) {
+                errors.push(InvalidIndex {
+                    element,
+                    number,
+                    line: lines.get(number).cloned().unwrap_or(0),
+                    attribute,
+                    index
+                });
+            }
+        };
+
+        for (number, &p) in self.points.iter().enumerate() {
+            check(Element::Point, number, &self.source_lines.points, Attribute::Position, p);
+        }
+
+        let lines = &self.source_lines.lines;
+        for (number, line) in self.lines.iter().enumerate() {
+            match *line {
+                Line::P(ref vertices) => for &p in vertices {
+                    check(Element::Line, number, lines, Attribute::Position, p as usize);
+                },
+                Line::PT(ref vertices) => for &(p, t) in vertices {
+                    check(Element::Line, number, lines, Attribute::Position, p as usize);
+                    check(Element::Line, number, lines, Attribute::TexCoord, t as usize);
+                },
+            }
+        }
+
+        let lines = &self.source_lines.polygons;
+        for (number, polygon) in self.polygons.iter().enumerate() {
+            match *polygon {
+                Polygon::P(ref vertices) => for &p in vertices {
+                    check(Element::Polygon, number, lines, Attribute::Position, p as usize);
+                },
+                Polygon::PT(ref vertices) => for &(p, t) in vertices {
+                    check(Element::Polygon, number, lines, Attribute::Position, p as usize);
+                    check(Element::Polygon, number, lines, Attribute::TexCoord, t as usize);
+                },
+                Polygon::PN(ref vertices) => for &(p, u) in vertices {
+                    check(Element::Polygon, number, lines, Attribute::Position, p as usize);
+                    check(Element::Polygon, number, lines, Attribute::Normal, u as usize);
+                },
+                Polygon::PTN(ref vertices) => for &(p, t, u) in vertices {
+                    check(Element::Polygon, number, lines, Attribute::Position, p as usize);
+                    check(Element::Polygon, number, lines, Attribute::TexCoord, t as usize);
+                    check(Element::Polygon, number, lines, Attribute::Normal, u as usize);
+                },
+            }
+        }
+
+        errors
+    }
+}
+
+/// Material, groups and object which contain an element, returned by `AttributeIndex::polygon`
+/// and the others.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementAttributes<'a> {
+    /// Name of the material, if the element follows a `usemtl` statement.
+    pub material: Option<&'a str>,
+    /// Smoothing group, if smoothing is on.
+    pub smoothing_group: Option<usize>,
+    /// Merging group, if merging is on.
+    pub merging_group: Option<usize>,
+    /// Names of the groups, in the order of `Obj::groups`.
+    pub groups: Vec<&'a str>,
+    /// Name of the object, if the element follows an `o` statement.
+    pub object: Option<&'a str>,
+}
+
+/// Index of the attributes of the elements of an `Obj`, returned by `Obj::attribute_index`.
+pub struct AttributeIndex<'a> {
+    obj: &'a Obj,
+    /// Intervals of the points, lines and polygons, in the order of `Element`.
+    elements: [Intervals; 3],
+}
+
+impl<'a> AttributeIndex<'a> {
+    /// Returns the material, groups and object which contain the point `index`, or `None` if
+    /// there is no such point.
+    pub fn point(&self, index: usize) -> Option<ElementAttributes<'a>> {
+        self.get(Element::Point, index)
+    }
+
+    /// Returns the material, groups and object which contain the line `index`, or `None` if
+    /// there is no such line.
+    pub fn line(&self, index: usize) -> Option<ElementAttributes<'a>> {
+        self.get(Element::Line, index)
+    }
+
+    /// Returns the material, groups and object which contain the polygon `index`, or `None` if
+    /// there is no such polygon.
+    pub fn polygon(&self, index: usize) -> Option<ElementAttributes<'a>> {
+        self.get(Element::Polygon, index)
+    }
+
+    /// Looks up the element `index` of the kind `element`.
+    fn get(&self, element: Element, index: usize) -> Option<ElementAttributes<'a>> {
+        let obj = self.obj;
+        let count = match element {
+            Element::Point => obj.points.len(),
+            Element::Line => obj.lines.len(),
+            Element::Polygon => obj.polygons.len(),
+        };
+        if index >= count { return None }
+
+        let interval = self.elements[element as usize].get(index);
+        Some(ElementAttributes {
+            material: interval.material.map(|i| obj.meshes.name(i)),
+            smoothing_group: interval.smoothing_group,
+            merging_group: interval.merging_group,
+            groups: interval.groups.iter().map(|&i| obj.groups.name(i)).collect(),
+            object: interval.object.map(|i| obj.objects.name(i)),
+        })
+    }
+}
+
+/// Sorted table of the intervals of elements which share the same attributes, built from the
+/// ranges of every group of an `Obj`.
+#[derive(Debug)]
+struct Intervals {
+    /// First element of each interval, in ascending order starting from 0.
+    starts: Vec<usize>,
+    /// Attributes of each interval.
+    intervals: Vec<Interval>,
+}
+
+/// Attributes of an interval of elements. Groups are identified by their positions in the
+/// `GroupMap`s of an `Obj`, and smoothing and merging groups by their keys.
+#[derive(Clone, Debug, Default)]
+struct Interval {
+    material: Option<usize>,
+    smoothing_group: Option<usize>,
+    merging_group: Option<usize>,
+    groups: Vec<usize>,
+    object: Option<usize>,
+}
+
+/// Kinds of groups which make up an `Interval`.
 #[derive(Clone, Copy)]
+enum Kind {
+    Material,
+    Smoothing,
+    Merging,
+    Group,
+    Object,
+}
+
+impl Intervals {
+    /// Builds the index from the ranges which are selected from each group of `obj` by `ranges`.
+    fn new(obj: &Obj, ranges: fn(&Group) -> &Vec<Range>) -> Self {
+        // Each range opens its group at the start and closes it at the end
+        let mut events = Vec::new();
+        let mut add = |kind: Kind, id: usize, group: &Group| {
+            for range in ranges(group).iter().filter(|range| range.start < range.end) {
+                events.push((range.start, true, kind as usize, id));
+                events.push((range.end, false, kind as usize, id));
+            }
+        };
+
+        for (name, group) in obj.material_meshes() {
+            add(Kind::Material, obj.meshes.indices[name], group);
+        }
+        for (&key, group) in obj.smoothing_groups.iter() {
+            add(Kind::Smoothing, key, group);
+        }
+        for (&key, group) in obj.merging_groups.iter() {
+            add(Kind::Merging, key, group);
+        }
+        for (i, (_, group)) in obj.groups.iter().enumerate() {
+            add(Kind::Group, i, group);
+        }
+        for (i, (_, group)) in obj.objects.iter().enumerate() {
+            add(Kind::Object, i, group);
+        }
+
+        // Ranges which end at a position are closed before those which start there are opened
+        events.sort_unstable_by_key(|&(position, open, _, _)| (position, open));
+
+        let mut index = Intervals { starts: vec![0], intervals: vec![Interval::default()] };
+        let mut active: [BTreeSet<usize>; 5] = Default::default();
+        let mut events = events.into_iter().peekable();
+        while let Some(&(position, _, _, _)) = events.peek() {
+            while let Some((_, open, kind, id)) = events.next_if(|event| event.0 == position) {
+                if open { active[kind].insert(id); } else { active[kind].remove(&id); }
+            }
+
+            // Lookups return the first group in the order of each map
+            let first = |kind: Kind| active[kind as usize].first().cloned();
+            let interval = Interval {
+                material: first(Kind::Material),
+                smoothing_group: first(Kind::Smoothing),
+                merging_group: first(Kind::Merging),
+                groups: active[Kind::Group as usize].iter().cloned().collect(),
+                object: first(Kind::Object),
+            };
+
+            if *index.starts.last().unwrap() == position {
+                *index.intervals.last_mut().unwrap() = interval;
+            } else {
+                index.starts.push(position);
+                index.intervals.push(interval);
+            }
+        }
+        index
+    }
+
+    /// Returns the attributes of the element `index`.
+    fn get(&self, index: usize) -> &Interval {
+        &self.intervals[self.starts.partition_point(|&start| start <= index) - 1]
+    }
+}
+
+/// Line numbers of the statements which defined the elements of an `Obj`, starting from 1.
+///
+/// A single `p` statement may define several points, which then share the same line number.
+#[derive(Clone, Debug, Default)]
+pub struct SourceLines {
+    /// Line number of each point.
+    pub points: Vec<usize>,
+    /// Line number of each line.
+    pub lines: Vec<usize>,
+    /// Line number of each polygon.
+    pub polygons: Vec<usize>,
+}
+
+/// A reference to a vector which does not exist, reported by `Obj::validate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidIndex {
+    /// Kind of the element which contains the reference.
+    pub element: Element,
+    /// Index of the element in `Obj::points`, `Obj::lines` or `Obj::polygons`.
+    pub number: usize,
+    /// Line number of the statement which defined the element. `0` if unknown.
+    pub line: usize,
+    /// Kind of the referenced vector.
+    pub attribute: Attribute,
+    /// The offending index, as stored in the element.
+    pub index: usize,
+}
+
+/// Kinds of the elements of an `Obj`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Element {
+    /// An element of `Obj::points`.
+    Point,
+    /// An element of `Obj::lines`.
+    Line,
+    /// An element of `Obj::polygons`.
+    Polygon,
+}
+
+/// Kinds of the vectors which are referenced by the elements of an `Obj`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attribute {
+    /// An element of `Obj::vertices`.
+    Position,
+    /// An element of `Obj::tex_coords`.
+    TexCoord,
+    /// An element of `Obj::normals`.
+    Normal,
+}
+
+/// A `call` statement which includes another file into this object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    /// Path of the file, as written in the `call` statement.
+    pub path: String,
+    /// Arguments which are passed to the file.
+    pub args: Vec<String>,
+}
+
+/// The `Point` type which stores the index of the position vector, starting from 1.
+pub type Point = usize;
+
+/// The `Line` type, which is a polyline of two or more vertices.
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Line {
-    /// A line which contains only the position data of both ends
-    P([u32; 2]),
-    /// A line which contains both position and texture coordinate data of both ends
-    PT([(u32, u32); 2])
+    /// A line which contains only the position data of each vertex.
+    P(Vec<u32>),
+    /// A line which contains both position and texture coordinate data of each vertex.
+    PT(Vec<(u32, u32)>)
 }
 
 /// The `Polygon` type.
@@ -473,6 +1084,26 @@ pub enum Polygon {
     PTN(Vec<(u32, u32, u32)>)
 }
 
+impl Polygon {
+    /// Converts the indices to start from 0, leaving `MISSING_INDEX` as is.
+    fn rebase_to_zero(&mut self) {
+        match self {
+            Polygon::P(vertices) => for p in vertices {
+                *p -= 1;
+            },
+            Polygon::PT(vertices) | Polygon::PN(vertices) => for (p, q) in vertices {
+                *p -= 1;
+                if *q != MISSING_INDEX { *q -= 1 }
+            },
+            Polygon::PTN(vertices) => for (p, t, u) in vertices {
+                *p -= 1;
+                if *t != MISSING_INDEX { *t -= 1 }
+                if *u != MISSING_INDEX { *u -= 1 }
+            },
+        }
+    }
+}
+
 /// A group which contains multiple range of points, lines and polygons
 #[derive(Clone, Debug)]
 pub struct Group {
@@ -494,6 +1125,63 @@ impl Group {
     }
 }
 
+/// Groups keyed by their names, which are kept in the order of their first appearance.
+#[derive(Clone, Debug, Default)]
+pub struct GroupMap {
+    entries: Vec<(String, Group)>,
+    indices: HashMap<String, usize>,
+}
+
+impl GroupMap {
+    /// Creates an empty `GroupMap`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the number of groups.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there is no group.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the group named `name`.
+    pub fn get(&self, name: &str) -> Option<&Group> {
+        self.indices.get(name).map(|&index| &self.entries[index].1)
+    }
+
+    /// Returns `true` if there is a group named `name`.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.indices.contains_key(name)
+    }
+
+    /// Iterates over the names of the groups, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| &name[..])
+    }
+
+    /// Iterates over the groups along with their names, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Group)> {
+        self.entries.iter().map(|(name, group)| (&name[..], group))
+    }
+
+    /// Returns the name of the group at `position` in the order.
+    fn name(&self, position: usize) -> &str {
+        &self.entries[position].0
+    }
+}
+
+impl Index<&str> for GroupMap {
+    type Output = Group;
+
+    fn index(&self, name: &str) -> &Group {
+        self.get(name).expect("no group found for name")
+    }
+}
+
 /// A struct which represent `[start, end)` range.
 #[derive(Clone, Copy, Debug)]
 pub struct Range {
@@ -502,3 +1190,10 @@ pub struct Range {
     /// The upper bound of the range (exclusive).
     pub end: usize
 }
+
+impl Range {
+    /// Returns `true` if `index` is within the range.
+    pub fn contains(&self, index: usize) -> bool {
+        self.start <= index && index < self.end
+    }
+}
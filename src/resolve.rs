@@ -0,0 +1,120 @@
+//! Resolves and opens the files which are referenced by `.obj` and `.mtl` files
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor};
+
+/// A storage backend from which `.obj` files and the resources they reference are loaded.
+///
+/// Resources are identified by `/`-separated paths, such as `models/cube.obj`.
+pub trait ResourceResolver {
+    /// Opens the resource at `path`.
+    fn open(&self, path: &str) -> io::Result<Box<dyn BufRead>>;
+
+    /// Returns the path of `reference`, which was referenced by the resource at `base`.
+    ///
+    /// By default, relative references are resolved against the directory of `base`. Both paths
+    /// may use `\` as the separator, and references starting with `/`, a drive letter such as
+    /// `C:` or a UNC prefix such as `\\server` are absolute.
+    fn resolve(&self, base: &str, reference: &str) -> String {
+        let base = base.replace('\\', "/");
+        let reference = reference.replace('\\', "/");
+        if is_absolute(&reference) { return normalize(&reference) }
+
+        match base.rfind('/') {
+            Some(index) => normalize(&format!("{}/{}", &base[..index], reference)),
+            None => normalize(&reference),
+        }
+    }
+}
+
+/// Tests whether `path`, whose separators are `/`, starts from a root, a drive letter or a UNC
+/// prefix such as `//server`.
+fn is_absolute(path: &str) -> bool {
+    let drive = match path.as_bytes() {
+        [letter, b':', ..] => letter.is_ascii_alphabetic(),
+        _ => false
+    };
+    path.starts_with('/') || drive
+}
+
+/// Removes `.` and `..` segments from `path`, where possible.
+fn normalize(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." => match segments.last() {
+                // Roots and drive letters are kept
+                Some(&last) if last != ".." && !last.is_empty() && !last.ends_with(':') => {
+                    segments.pop();
+                }
+                _ => segments.push(segment),
+            },
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+
+/// Opens resources from the filesystem.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FileResolver;
+
+impl ResourceResolver for FileResolver {
+    fn open(&self, path: &str) -> io::Result<Box<dyn BufRead>> {
+        let file = File::open(path)?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+
+/// Opens resources from an in-memory map from paths to their contents.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryResolver {
+    /// Contents of each resource.
+    pub files: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryResolver {
+    /// Constructs an empty `MemoryResolver`.
+    pub fn new() -> Self {
+        MemoryResolver { files: HashMap::new() }
+    }
+
+    /// Adds a resource at `path`.
+    pub fn insert(&mut self, path: &str, contents: Vec<u8>) {
+        self.files.insert(path.to_string(), contents);
+    }
+}
+
+impl ResourceResolver for MemoryResolver {
+    fn open(&self, path: &str) -> io::Result<Box<dyn BufRead>> {
+        match self.files.get(path) {
+            Some(contents) => Ok(Box::new(Cursor::new(contents.clone()))),
+            None => Err(io::Error::new(io::ErrorKind::NotFound,
+                                       format!("no such resource: {}", path))),
+        }
+    }
+}
+
+#[test]
+fn test_resolve() {
+    let resolver = MemoryResolver::new();
+
+    assert_eq!(resolver.resolve("cube.obj", "cube.mtl"), "cube.mtl");
+    assert_eq!(resolver.resolve("models/cube.obj", "cube.mtl"), "models/cube.mtl");
+    assert_eq!(resolver.resolve("models/cube.obj", "./mtl/cube.mtl"), "models/mtl/cube.mtl");
+    assert_eq!(resolver.resolve("models/cube.obj", "..\\textures\\a.png"), "textures/a.png");
+    assert_eq!(resolver.resolve("models/cube.obj", "../../a.png"), "../a.png");
+    assert_eq!(resolver.resolve("models/cube.obj", "/abs/a.png"), "/abs/a.png");
+
+    // Windows paths
+    assert_eq!(resolver.resolve("models/cube.obj", "C:\\tex\\a.png"), "C:/tex/a.png");
+    assert_eq!(resolver.resolve("models/cube.obj", "c:/tex/../a.png"), "c:/a.png");
+    assert_eq!(resolver.resolve("models/cube.obj", "\\\\server\\share\\a.png"),
+               "//server/share/a.png");
+    assert_eq!(resolver.resolve("models\\cube.obj", "cube.mtl"), "models/cube.mtl");
+    assert_eq!(resolver.resolve("C:\\models\\cube.obj", "..\\a.png"), "C:/a.png");
+}
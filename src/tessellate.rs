@@ -0,0 +1,676 @@
+//! Approximates the free-form surfaces of `.obj` format with polygons
+
+use std::collections::HashMap;
+
+use crate::curve::CurveEvaluator;
+use crate::freeform::{Basis, CurveSegment, Surface, SurfaceTechnique};
+use crate::nurbs;
+use crate::obj::{Obj, Polygon, Range};
+
+/// Tessellates every B-spline, rational B-spline and Bezier surface of `obj` into triangles.
+///
+/// Each surface is divided into a grid in its parameter space, which follows the `stech`
+/// statement in effect for the surface unless `options.tolerance` is given. Triangles of the grid
+/// which cross the `trim` and `hole` loops are cut along them, and the parts outside the `trim`
+/// loops or inside the `hole` loops are dropped, so trimmed edges follow the trimming curves.
+///
+/// If `options.stitch` is set, surfaces which are connected by `con` statements share the
+/// vertices along their common edges, so that no cracks appear between them.
+pub fn tessellate(obj: &Obj, options: &TessellateOptions) -> Tessellation {
+    let mut result = Tessellation::default();
+
+    let patches: Vec<_> = obj.surfaces.iter().map(|surface| Patch::new(obj, surface)).collect();
+    let mut samples: Vec<_> = patches.iter().map(|patch| match *patch {
+        Some(ref patch) => patch.grid(options),
+        None => [Vec::new(), Vec::new()],
+    }).collect();
+
+    let edges = if options.stitch { edges(obj, &patches) } else { Vec::new() };
+    align(&edges, &mut samples);
+
+    let mut grids = Vec::with_capacity(patches.len());
+    for (surface, patch) in patches.iter().enumerate() {
+        let start = result.polygons.len();
+        let grid = match *patch {
+            Some(ref patch) => {
+                let links = links(&edges, surface, &samples, &grids);
+                patch.tessellate(obj, &samples[surface], &links, &mut result)
+            }
+            None => Vec::new()
+        };
+        grids.push(grid);
+        result.surfaces.push(Range { start, end: result.polygons.len() });
+    }
+
+    result
+}
+
+
+/// Options for `tessellate`.
+#[derive(Clone, Debug)]
+pub struct TessellateOptions {
+    /// Maximum distance between the triangles and the surfaces. If given, it overrides the `stech`
+    /// statements of the surfaces.
+    pub tolerance: Option<f32>,
+    /// Number of segments per knot span in both directions, for surfaces which have neither
+    /// `stech` statement nor `tolerance`.
+    pub resolution: u32,
+    /// Whether to share the vertices along the edges which are connected by `con` statements.
+    ///
+    /// Edges are stitched only if their curves run along iso-parametric lines of both surfaces,
+    /// such as the boundaries of their domains.
+    pub stitch: bool,
+}
+
+impl Default for TessellateOptions {
+    fn default() -> Self {
+        TessellateOptions {
+            tolerance: None,
+            resolution: 4,
+            stitch: false
+        }
+    }
+}
+
+/// Triangles which approximate the free-form surfaces of an `Obj`.
+#[derive(Clone, Debug, Default)]
+pub struct Tessellation {
+    /// Position vectors of the vertices of the triangles.
+    pub vertices: Vec<[f32; 4]>,
+    /// Triangles which index `vertices`, starting from 0 if `Obj::zero_based` is set or from 1
+    /// otherwise.
+    pub polygons: Vec<Polygon>,
+    /// Range of `polygons` which approximates each of `Obj::surfaces`. The range is empty if the
+    /// surface could not be evaluated, e.g. because of its basis or an invalid knot vector.
+    pub surfaces: Vec<Range>,
+}
+
+
+/// Upper limit of the number of segments per knot span, to bound adaptive subdivision.
+const MAX_SEGMENTS: usize = 256;
+
+/// Number of segments into which each knot span of a trimming loop is sampled.
+const TRIM_SAMPLES: usize = 32;
+
+/// Distance in the parameter space, relative to the size of the domain, within which points are
+/// regarded as lying on a trimming loop.
+const CUT_TOLERANCE: f32 = 1e-6;
+
+/// Largest difference between parameter values which are regarded as the same.
+const EPSILON: f32 = 1e-4;
+
+/// Number of iterations to find the parameter value on the curve of an `Edge`.
+const BISECTIONS: usize = 32;
+
+/// A surface which is ready to be evaluated.
+struct Patch<'a> {
+    surface: &'a Surface,
+    knots: [Vec<f32>; 2],
+    degree: [usize; 2],
+    counts: [usize; 2],
+    points: Vec<[f32; 4]>,
+    domain: [[f32; 2]; 2],
+}
+
+impl<'a> Patch<'a> {
+    /// Collects the knot vectors and the control points of `surface`. Returns `None` if they are
+    /// inconsistent, or the surface is neither a B-spline nor a Bezier surface.
+    fn new(obj: &Obj, surface: &'a Surface) -> Option<Self> {
+        let attributes = &surface.attributes;
+        let degree = [attributes.degree[0] as usize, attributes.degree[1] as usize];
+
+        let indices = positions(&surface.control_points);
+        let (knots, counts) = match attributes.basis? {
+            Basis::BSpline => {
+                let knots = surface.parameters.clone();
+                let count_u = knots[0].len().checked_sub(degree[0] + 1)?;
+                let count_v = knots[1].len().checked_sub(degree[1] + 1)?;
+                (knots, [count_u, count_v])
+            }
+            Basis::Bezier => {
+                // Without `parm` statements, the surface consists of a single segment
+                let count = |axis: usize| match surface.parameters[axis].len() {
+                    0 => degree[axis].checked_add(1),
+                    len => (len - 1).checked_mul(degree[axis])?.checked_add(1),
+                };
+                let counts = [count(0)?, count(1)?];
+
+                // The size of the knot vectors depends on the degree, which must be checked
+                // against the control points before allocating them
+                if counts[0].checked_mul(counts[1]) != Some(indices.len()) { return None }
+                let knots = [
+                    nurbs::bezier_knots(degree[0], counts[0], &surface.parameters[0])?,
+                    nurbs::bezier_knots(degree[1], counts[1], &surface.parameters[1])?,
+                ];
+                (knots, counts)
+            }
+            _ => return None
+        };
+
+        if counts[0].checked_mul(counts[1]) != Some(indices.len()) { return None }
+        for axis in 0..2 {
+            if !nurbs::is_valid(&knots[axis], degree[axis], counts[axis]) { return None }
+        }
+
+        let mut points = Vec::with_capacity(indices.len());
+        for index in indices {
+            let vertex = obj.vertices.get(obj.position(index as usize)?)?;
+            let w = if attributes.rational { vertex[3] } else { 1.0 };
+            points.push([vertex[0] * w, vertex[1] * w, vertex[2] * w, w]);
+        }
+
+        let mut domain = [[0.0; 2]; 2];
+        for axis in 0..2 {
+            let [start, end] = nurbs::domain(&knots[axis], degree[axis], counts[axis]);
+            let range = surface.range[axis];
+            domain[axis] = if range[0] < range[1] {
+                [range[0].max(start), range[1].min(end)]
+            } else {
+                [start, end]
+            };
+            if domain[axis][0] >= domain[axis][1] { return None }
+        }
+
+        Some(Patch { surface, knots, degree, counts, points, domain })
+    }
+
+    /// Evaluates the position at `(u, v)`.
+    fn evaluate(&self, u: f32, v: f32) -> [f32; 3] {
+        let knots = [&self.knots[0][..], &self.knots[1][..]];
+        nurbs::project(nurbs::surface_point(knots, self.degree, self.counts, &self.points, u, v))
+    }
+
+    /// Evaluates the position on the iso-parametric curve along `axis`, at `t` on the axis and
+    /// `s` on the other axis.
+    fn evaluate_along(&self, axis: usize, t: f32, s: f32) -> [f32; 3] {
+        if axis == 0 { self.evaluate(t, s) } else { self.evaluate(s, t) }
+    }
+
+    /// Returns the parameter values in both directions at which the surface is sampled.
+    fn grid(&self, options: &TessellateOptions) -> [Vec<f32>; 2] {
+        let technique = match options.tolerance {
+            Some(tolerance) => SurfaceTechnique::Curvature {
+                max_distance: tolerance,
+                max_angle: 180.0
+            },
+            None => self.surface.attributes.surface_technique.unwrap_or({
+                let resolution = options.resolution as f32;
+                SurfaceTechnique::ConstantParametric { resolution: [resolution, resolution] }
+            }),
+        };
+        [self.samples(0, technique), self.samples(1, technique)]
+    }
+
+    /// Appends the triangles which approximate the surface on the grid of `samples` to `result`,
+    /// and returns the index of the vertex at each point of the grid, if any.
+    ///
+    /// Points of the grid which are in `links` share the vertices of other points.
+    fn tessellate(&self, obj: &Obj, samples: &[Vec<f32>; 2], links: &HashMap<usize, Link>,
+                  result: &mut Tessellation) -> Vec<Option<u32>>
+    {
+        let trims = loops(obj, &self.surface.trims);
+        let holes = loops(obj, &self.surface.holes);
+        let inside = |point: [f32; 2]| {
+            (trims.is_empty() || trims.iter().any(|trim| contains(trim, point))) &&
+                !holes.iter().any(|hole| contains(hole, point))
+        };
+
+        // Vertices are added on demand, so that trimmed regions leave no unused vertices
+        let base = obj.index_base();
+        let width = samples[0].len();
+        let mut indices: Vec<Option<u32>> = vec![None; width * samples[1].len()];
+        let mut vertex = |i: usize, j: usize, vertices: &mut Vec<[f32; 4]>| {
+            let point = j * width + i;
+            let target = match links.get(&point) {
+                Some(&Link::Point(target)) => target,
+                _ => point
+            };
+            let index = match links.get(&target) {
+                Some(&Link::Vertex(index)) => *indices[target].get_or_insert(index),
+                _ => *indices[target].get_or_insert_with(|| {
+                    let [x, y, z] = self.evaluate(samples[0][target % width],
+                                                  samples[1][target / width]);
+                    vertices.push([x, y, z, 1.0]);
+                    (vertices.len() - 1 + base) as u32
+                }),
+            };
+            indices[point] = Some(index);
+            index
+        };
+
+        // Sides of the loops, which cut the triangles crossing them into convex pieces
+        let cuts: Vec<_> = trims.iter().chain(holes.iter()).flat_map(|polygon| {
+            (0..polygon.len()).map(move |k| [polygon[k], polygon[(k + 1) % polygon.len()]])
+        }).filter(|&[a, b]| a != b).collect();
+        let [u, v] = self.domain;
+        let tolerance = (u[1] - u[0]).max(v[1] - v[0]) * CUT_TOLERANCE;
+
+        // Points where the loops cut the grid are shared by the pieces on both sides
+        let mut cut_vertices: HashMap<[i64; 2], u32> = HashMap::new();
+        let mut cut_vertex = |[u, v]: [f32; 2], vertices: &mut Vec<[f32; 4]>| {
+            let key = [(u / tolerance).round() as i64, (v / tolerance).round() as i64];
+            *cut_vertices.entry(key).or_insert_with(|| {
+                let [x, y, z] = self.evaluate(u, v);
+                vertices.push([x, y, z, 1.0]);
+                (vertices.len() - 1 + base) as u32
+            })
+        };
+
+        for j in 0..samples[1].len() - 1 {
+            for i in 0..width - 1 {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                for triangle in [[0, 1, 2], [0, 2, 3]] {
+                    let triangle: Vec<Corner> = triangle.iter().map(|&k| {
+                        let (i, j) = corners[k];
+                        ([samples[0][i], samples[1][j]], Some((i, j)))
+                    }).collect();
+
+                    for piece in cut(triangle, &cuts, tolerance) {
+                        let mut centroid = [0.0; 2];
+                        for &([u, v], _) in piece.iter() {
+                            centroid[0] += u / piece.len() as f32;
+                            centroid[1] += v / piece.len() as f32;
+                        }
+                        if !inside(centroid) { continue }
+
+                        let indices: Vec<u32> = piece.iter().map(|&(point, corner)| match corner {
+                            Some((i, j)) => vertex(i, j, &mut result.vertices),
+                            None => cut_vertex(point, &mut result.vertices),
+                        }).collect();
+                        for k in 1..indices.len() - 1 {
+                            let polygon = vec![indices[0], indices[k], indices[k + 1]];
+                            result.polygons.push(Polygon::P(polygon));
+                        }
+                    }
+                }
+            }
+        }
+
+        indices
+    }
+
+    /// Returns the parameter values along `axis` at which the surface is sampled.
+    fn samples(&self, axis: usize, technique: SurfaceTechnique) -> Vec<f32> {
+        let breakpoints = nurbs::breakpoints(&self.knots[axis], self.domain[axis]);
+
+        // Iso-parametric curves at the ends and the middle of each span of the other axis
+        let other = nurbs::breakpoints(&self.knots[1 - axis], self.domain[1 - axis]);
+        let mut isolines = other.clone();
+        isolines.extend(other.windows(2).map(|pair| (pair[0] + pair[1]) / 2.0));
+
+        let mut samples = Vec::new();
+        for span in breakpoints.windows(2) {
+            let (start, end) = (span[0], span[1]);
+            let segments = match technique {
+                SurfaceTechnique::ConstantParametric { resolution } => resolution[axis] as usize,
+                SurfaceTechnique::ConstantParametricTrimmed { resolution } => resolution as usize,
+                SurfaceTechnique::ConstantSpace { max_length } => {
+                    isolines.iter().map(|&s| {
+                        let curve = |t| self.evaluate_along(axis, t, s);
+                        (length(&curve, start, end) / max_length).ceil() as usize
+                    }).max().unwrap_or(1)
+                }
+                SurfaceTechnique::Curvature { max_distance, max_angle } => {
+                    isolines.iter().map(|&s| {
+                        let curve = |t| self.evaluate_along(axis, t, s);
+                        subdivide(&curve, start, end, max_distance, max_angle)
+                    }).max().unwrap_or(1)
+                }
+            };
+
+            let segments = segments.clamp(1, MAX_SEGMENTS);
+            for k in 0..segments {
+                samples.push(start + (end - start) * k as f32 / segments as f32);
+            }
+        }
+        samples.push(*breakpoints.last().unwrap());
+        samples
+    }
+}
+
+/// Returns the indices of the position vectors of `polygon`.
+fn positions(polygon: &Polygon) -> Vec<u32> {
+    match *polygon {
+        Polygon::P(ref vertices) => vertices.clone(),
+        Polygon::PT(ref vertices) | Polygon::PN(ref vertices) => {
+            vertices.iter().map(|&(p, _)| p).collect()
+        }
+        Polygon::PTN(ref vertices) => vertices.iter().map(|&(p, _, _)| p).collect(),
+    }
+}
+
+/// Approximates the length of `curve` between `start` and `end`.
+fn length<F: Fn(f32) -> [f32; 3]>(curve: &F, start: f32, end: f32) -> f32 {
+    const STEPS: usize = 16;
+    let mut previous = curve(start);
+    let mut length = 0.0;
+    for k in 1..STEPS + 1 {
+        let point = curve(start + (end - start) * k as f32 / STEPS as f32);
+        length += nurbs::distance(previous, point);
+        previous = point;
+    }
+    length
+}
+
+/// Returns the number of segments of the same parametric length into which `curve` between
+/// `start` and `end` has to be divided, so that the middle of each segment deviates from its
+/// chord by at most `max_distance`, and adjacent halves of each segment turn by at most
+/// `max_angle` degrees.
+pub(crate) fn subdivide<F>(curve: &F, start: f32, end: f32, max_distance: f32, max_angle: f32)
+    -> usize where F: Fn(f32) -> [f32; 3]
+{
+    let max_angle = max_angle.to_radians();
+    let mut segments = 1;
+    while segments < MAX_SEGMENTS {
+        let step = (end - start) / segments as f32;
+        let flat = (0..segments).all(|k| {
+            let t = start + step * k as f32;
+            let (a, m, b) = (curve(t), curve(t + step / 2.0), curve(t + step));
+            nurbs::deviation(a, b, m) <= max_distance && nurbs::angle(a, m, b) <= max_angle
+        });
+        if flat { break }
+        segments *= 2;
+    }
+    segments
+}
+
+/// Samples the loops of `CurveSegment`s in the parameter space of a surface into polygons.
+fn loops(obj: &Obj, loops: &[Vec<CurveSegment>]) -> Vec<Vec<[f32; 2]>> {
+    loops.iter().map(|segments| {
+        let mut polygon = Vec::new();
+        for segment in segments {
+            let curve = obj.position(segment.curve).and_then(|i| obj.curves_2d.get(i));
+            let curve = match curve.and_then(|curve| CurveEvaluator::new_2d(obj, curve)) {
+                Some(curve) => curve,
+                None => continue
+            };
+
+            // Spans are sampled separately, so that corners at their ends are kept
+            let breakpoints = curve.breakpoints(segment.range);
+            for span in breakpoints.windows(2) {
+                let (start, end) = (span[0], span[1]);
+                for k in 0..TRIM_SAMPLES {
+                    let t = start + (end - start) * k as f32 / TRIM_SAMPLES as f32;
+                    let [u, v, _] = curve.evaluate(t);
+                    polygon.push([u, v]);
+                }
+            }
+            let [u, v, _] = curve.evaluate(segment.range[1]);
+            polygon.push([u, v]);
+        }
+        polygon
+    }).filter(|polygon| polygon.len() >= 3).collect()
+}
+
+/// A curve of a `Connection` which runs along an iso-parametric line of its surface.
+struct Edge {
+    /// Index of the surface in `Obj::surfaces`, starting from 0.
+    surface: usize,
+    /// Axis along which the curve runs, `0` for `u` and `1` for `v`.
+    axis: usize,
+    /// Parameter value of the line on the other axis.
+    value: f32,
+    curve: CurveEvaluator,
+    range: [f32; 2],
+}
+
+impl Edge {
+    /// Prepares `segment` on the surface `surface` for stitching. Returns `None` if its curve can
+    /// not be evaluated or is not iso-parametric.
+    fn new(obj: &Obj, surface: usize, segment: &CurveSegment) -> Option<Self> {
+        let curve = obj.curves_2d.get(obj.position(segment.curve)?)?;
+        let curve = CurveEvaluator::new_2d(obj, curve)?;
+
+        let [start, end] = segment.range;
+        let points = [start, (start + end) / 2.0, end].map(|t| curve.evaluate(t));
+        let axis = (0..2).find(|&axis| {
+            let fixed = 1 - axis;
+            points.iter().all(|point| (point[fixed] - points[0][fixed]).abs() <= EPSILON) &&
+                (points[2][axis] - points[0][axis]).abs() > EPSILON
+        })?;
+
+        Some(Edge { surface, axis, value: points[0][1 - axis], curve, range: segment.range })
+    }
+
+    /// Returns the parameter value of the surface along `axis` at `t` on the curve.
+    fn position(&self, t: f32) -> f32 {
+        self.curve.evaluate(t)[self.axis]
+    }
+
+    /// Finds the parameter value on the curve at which the surface has `position` along `axis`,
+    /// assuming that the position changes monotonically along the curve.
+    fn parameter(&self, position: f32) -> Option<f32> {
+        let (mut low, mut high) = (self.range[0], self.range[1]);
+        let (first, last) = (self.position(low), self.position(high));
+        if position < first.min(last) - EPSILON || position > first.max(last) + EPSILON {
+            return None
+        }
+
+        let increasing = first < last;
+        for _ in 0..BISECTIONS {
+            let middle = (low + high) / 2.0;
+            if (self.position(middle) < position) == increasing {
+                low = middle
+            } else {
+                high = middle
+            }
+        }
+        Some((low + high) / 2.0)
+    }
+
+    /// Maps `position` along this edge to the position along `other`, assuming that the ranges
+    /// of both curves correspond linearly.
+    fn transfer(&self, other: &Edge, position: f32) -> Option<f32> {
+        let ratio = (self.parameter(position)? - self.range[0]) / (self.range[1] - self.range[0]);
+        Some(other.position(other.range[0] + (other.range[1] - other.range[0]) * ratio))
+    }
+}
+
+/// Reference from a point of a grid to the vertex which it shares.
+enum Link {
+    /// A vertex of another surface.
+    Vertex(u32),
+    /// Another point of the same grid.
+    Point(usize),
+}
+
+/// Collects the pairs of edges connected by `Obj::connections`, between surfaces which can be
+/// tessellated.
+fn edges(obj: &Obj, patches: &[Option<Patch>]) -> Vec<[Edge; 2]> {
+    obj.connections.iter().filter_map(|connection| {
+        let edge = |k: usize| {
+            let surface = obj.position(connection.surfaces[k])?;
+            patches.get(surface)?.as_ref()?;
+            Edge::new(obj, surface, &connection.curves[k])
+        };
+        Some([edge(0)?, edge(1)?])
+    }).collect()
+}
+
+/// Adds samples to the grids of the surfaces, so that both edges of each pair are sampled at the
+/// same points.
+fn align(edges: &[[Edge; 2]], samples: &mut [[Vec<f32>; 2]]) {
+    for edge in edges.iter().flatten() {
+        insert(&mut samples[edge.surface][1 - edge.axis], edge.value);
+    }
+
+    // Samples span the whole grid, so those added for one edge may reach another edge
+    for _ in 0..edges.len() + 1 {
+        let mut changed = false;
+        for pair in edges {
+            for k in 0..2 {
+                let (from, to) = (&pair[k], &pair[1 - k]);
+                let positions: Vec<_> = samples[from.surface][from.axis].iter()
+                    .filter_map(|&position| from.transfer(to, position))
+                    .collect();
+                for position in positions {
+                    changed |= insert(&mut samples[to.surface][to.axis], position);
+                }
+            }
+        }
+        if !changed { break }
+    }
+}
+
+/// Finds the points of the grid of `surface` which share the vertices of the surfaces
+/// tessellated earlier, or of other points of its own grid.
+fn links(edges: &[[Edge; 2]], surface: usize, samples: &[[Vec<f32>; 2]],
+         grids: &[Vec<Option<u32>>]) -> HashMap<usize, Link>
+{
+    let point = |edge: &Edge, n: usize| {
+        let samples = &samples[edge.surface];
+        let fixed = find(&samples[1 - edge.axis], edge.value)?;
+        let (i, j) = if edge.axis == 0 { (n, fixed) } else { (fixed, n) };
+        Some(j * samples[0].len() + i)
+    };
+
+    let mut links = HashMap::new();
+    for pair in edges {
+        for k in 0..2 {
+            // Vertices belong to the surface tessellated first, or to the first edge of a pair
+            // on the same surface
+            let (own, other) = (&pair[k], &pair[1 - k]);
+            if own.surface != surface || other.surface > surface { continue }
+            if other.surface == surface && k == 0 { continue }
+
+            for (n, &position) in samples[surface][own.axis].iter().enumerate() {
+                let m = own.transfer(other, position)
+                    .and_then(|position| find(&samples[other.surface][other.axis], position));
+                let (a, b) = match (point(own, n), m.and_then(|m| point(other, m))) {
+                    (Some(a), Some(b)) => (a, b),
+                    _ => continue
+                };
+
+                if other.surface == surface {
+                    if a != b { links.insert(a, Link::Point(b)); }
+                } else if let Some(index) = grids[other.surface][b] {
+                    links.insert(a, Link::Vertex(index));
+                }
+            }
+        }
+    }
+    links
+}
+
+/// Inserts `value` into the sorted `samples`, unless it is out of their range or close to one of
+/// them. Returns whether it was inserted.
+fn insert(samples: &mut Vec<f32>, value: f32) -> bool {
+    if value < samples[0] || value > samples[samples.len() - 1] { return false }
+    if find(samples, value).is_some() { return false }
+
+    let i = samples.partition_point(|&sample| sample < value);
+    samples.insert(i, value);
+    true
+}
+
+/// Finds the index of the sample which is close to `value`.
+fn find(samples: &[f32], value: f32) -> Option<usize> {
+    samples.iter().position(|&sample| (sample - value).abs() <= EPSILON)
+}
+
+/// A vertex of a piece of a grid triangle in the parameter space, along with the point of the
+/// grid if it is one of them.
+type Corner = ([f32; 2], Option<(usize, usize)>);
+
+/// Cuts the convex, counterclockwise `polygon` by the segments of `cuts` which cross it, into
+/// convex pieces which no segment crosses.
+fn cut(polygon: Vec<Corner>, cuts: &[[[f32; 2]; 2]], tolerance: f32) -> Vec<Vec<Corner>> {
+    let (mut low, mut high) = ([f32::MAX; 2], [f32::MIN; 2]);
+    for &(point, _) in polygon.iter() {
+        for k in 0..2 {
+            low[k] = low[k].min(point[k]);
+            high[k] = high[k].max(point[k]);
+        }
+    }
+
+    let mut pieces = vec![polygon];
+    for &[a, b] in cuts {
+        let outside = (0..2).any(|k| a[k].max(b[k]) < low[k] || a[k].min(b[k]) > high[k]);
+        if outside { continue }
+
+        let mut next = Vec::with_capacity(pieces.len() + 1);
+        for piece in pieces {
+            if crosses(&piece, a, b, tolerance) {
+                next.extend(split(&piece, a, b, tolerance));
+            } else {
+                next.push(piece);
+            }
+        }
+        pieces = next;
+    }
+    pieces
+}
+
+/// Tests whether the segment from `a` to `b` crosses the interior of the convex, counterclockwise
+/// `piece`, by more than `tolerance`.
+fn crosses(piece: &[Corner], a: [f32; 2], b: [f32; 2], tolerance: f32) -> bool {
+    let d = [b[0] - a[0], b[1] - a[1]];
+    let length = d[0].hypot(d[1]);
+    let cross = |e: [f32; 2], p: [f32; 2]| e[0] * p[1] - e[1] * p[0];
+
+    // The line must separate the vertices of the piece
+    let sides: Vec<f32> = piece.iter()
+        .map(|&(p, _)| cross(d, [p[0] - a[0], p[1] - a[1]]) / length)
+        .collect();
+    if !sides.iter().any(|&side| side > tolerance) || !sides.iter().any(|&side| side < -tolerance) {
+        return false
+    }
+
+    // Clip the segment to the piece, whose interior is on the left of each edge
+    let (mut start, mut end) = (0.0f32, 1.0f32);
+    for k in 0..piece.len() {
+        let (p, q) = (piece[k].0, piece[(k + 1) % piece.len()].0);
+        let e = [q[0] - p[0], q[1] - p[1]];
+        let f = cross(e, [a[0] - p[0], a[1] - p[1]]);
+        let df = cross(e, d);
+        if df == 0.0 {
+            if f < 0.0 { return false }
+        } else if df > 0.0 {
+            start = start.max(-f / df);
+        } else {
+            end = end.min(-f / df);
+        }
+    }
+    (end - start) * length > tolerance
+}
+
+/// Splits the convex `piece` into the parts on the left and the right of the line through `a`
+/// and `b`. Vertices within `tolerance` of the line belong to both parts.
+fn split(piece: &[Corner], a: [f32; 2], b: [f32; 2], tolerance: f32) -> [Vec<Corner>; 2] {
+    let d = [b[0] - a[0], b[1] - a[1]];
+    let length = d[0].hypot(d[1]);
+    let side = |p: [f32; 2]| {
+        let side = (d[0] * (p[1] - a[1]) - d[1] * (p[0] - a[0])) / length;
+        if side.abs() <= tolerance { 0.0 } else { side }
+    };
+
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    for k in 0..piece.len() {
+        let (p, q) = (piece[k], piece[(k + 1) % piece.len()]);
+        let (sp, sq) = (side(p.0), side(q.0));
+        if sp >= 0.0 { left.push(p) }
+        if sp <= 0.0 { right.push(p) }
+        if sp * sq < 0.0 {
+            let t = sp / (sp - sq);
+            let point = [p.0[0] + (q.0[0] - p.0[0]) * t, p.0[1] + (q.0[1] - p.0[1]) * t];
+            left.push((point, None));
+            right.push((point, None));
+        }
+    }
+    [left, right]
+}
+
+/// Tests whether `point` is inside `polygon` with the even-odd rule.
+fn contains(polygon: &[[f32; 2]], point: [f32; 2]) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &current in polygon {
+        if (current[1] > point[1]) != (previous[1] > point[1]) {
+            let x = current[0] + (point[1] - current[1]) * (previous[0] - current[0]) /
+                (previous[1] - current[1]);
+            if point[0] < x { inside = !inside }
+        }
+        previous = current;
+    }
+    inside
+}
@@ -0,0 +1,128 @@
+use obj::{load_obj, CurveEvaluator};
+
+fn near(a: [f32; 3], b: [f32; 3]) -> bool {
+    (0..3).all(|k| (a[k] - b[k]).abs() < 1e-4)
+}
+
+#[test]
+fn bezier() {
+    let input = "v 0 0 0\nv 1 2 0\nv 3 2 0\nv 4 0 0\ncstype bezier\ndeg 3\ncurv 0 1 1 2 3 4\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let curve = CurveEvaluator::new(&obj, &obj.curves[0]).unwrap();
+
+    assert_eq!(curve.domain(), [0.0, 1.0]);
+    assert!(near(curve.evaluate(0.0), [0.0, 0.0, 0.0]));
+    assert!(near(curve.evaluate(0.5), [2.0, 1.5, 0.0]));
+    assert!(near(curve.evaluate(1.0), [4.0, 0.0, 0.0]));
+    assert!(near(curve.derivative(0.0), [3.0, 6.0, 0.0]));
+    assert!(near(curve.derivative(1.0), [3.0, -6.0, 0.0]));
+
+    // The same curve, given with its basis matrix
+    let input = "v 0 0 0\nv 1 2 0\nv 3 2 0\nv 4 0 0\ncstype bmatrix\ndeg 3\nstep 3\n\
+                 bmat u 1 -3 3 -1 \\\n0 3 -6 3 \\\n0 0 3 -3 \\\n0 0 0 1\n\
+                 curv 0 1 1 2 3 4\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let matrix = CurveEvaluator::new(&obj, &obj.curves[0]).unwrap();
+    for &t in &[0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert!(near(matrix.evaluate(t), curve.evaluate(t)));
+        assert!(near(matrix.derivative(t), curve.derivative(t)));
+    }
+}
+
+#[test]
+fn cardinal() {
+    let input = "v 0 0 0\nv 1 0 0\nv 2 1 0\nv 3 1 0\nv 4 0 0\n\
+                 cstype cardinal\ndeg 3\ncurv 0 2 1 2 3 4 5\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let curve = CurveEvaluator::new(&obj, &obj.curves[0]).unwrap();
+
+    // The curve interpolates the inner control points at the ends of its segments
+    assert_eq!(curve.domain(), [0.0, 2.0]);
+    assert!(near(curve.evaluate(0.0), [1.0, 0.0, 0.0]));
+    assert!(near(curve.evaluate(1.0), [2.0, 1.0, 0.0]));
+    assert!(near(curve.evaluate(2.0), [3.0, 1.0, 0.0]));
+    assert!(near(curve.derivative(1.0), [1.0, 0.5, 0.0]));
+}
+
+#[test]
+fn taylor() {
+    // x = 1 + s, y = s^2 for the local parameter s of the segment
+    let input = "v 1 0 0\nv 1 0 0\nv 0 1 0\ncstype taylor\ndeg 2\ncurv 0 2 1 2 3\n\
+                 parm u 0 2\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let curve = CurveEvaluator::new(&obj, &obj.curves[0]).unwrap();
+
+    // The segment is mapped to `0..2`, so that `s = t / 2`
+    assert!(near(curve.evaluate(1.0), [1.5, 0.25, 0.0]));
+    assert!(near(curve.derivative(1.0), [0.5, 0.5, 0.0]));
+}
+
+#[test]
+fn bspline() {
+    let input = "v 0 0 0\nv 1 2 0\nv 2 -1 1\nv 3 1 0\nv 4 0 2\ncstype bspline\ndeg 3\n\
+                 curv 0 2 1 2 3 4 5\nparm u 0 0 0 0 1 2 2 2 2\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let curve = CurveEvaluator::new(&obj, &obj.curves[0]).unwrap();
+
+    let h = 1e-3;
+    for &t in &[0.3, 0.8, 1.5] {
+        let (a, b) = (curve.evaluate(t - h), curve.evaluate(t + h));
+        let derivative = curve.derivative(t);
+        assert!((0..3).all(|k| ((b[k] - a[k]) / (2.0 * h) - derivative[k]).abs() < 1e-2));
+    }
+
+    // Only the range given in the `curv` statement is evaluated
+    let input = input.replace("curv 0 2", "curv 0.5 1");
+    let obj = load_obj(input.as_bytes()).unwrap();
+    assert_eq!(CurveEvaluator::new(&obj, &obj.curves[0]).unwrap().domain(), [0.5, 1.0]);
+}
+
+#[test]
+fn flatten() {
+    // A quarter of the unit circle
+    let w = 0.5f32.sqrt();
+    let input = format!("v 1 0 0\nv 1 1 0 {w}\nv 0 1 0\ncstype rat bspline\ndeg 2\n\
+                         curv 0 1 1 2 3\nparm u 0 0 0 1 1 1\nend\n", w = w);
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let curve = CurveEvaluator::new(&obj, &obj.curves[0]).unwrap();
+
+    let tolerance = 1e-3;
+    let polyline = curve.flatten(tolerance);
+    assert!(near(polyline[0], [1.0, 0.0, 0.0]));
+    assert!(near(*polyline.last().unwrap(), [0.0, 1.0, 0.0]));
+    for pair in polyline.windows(2) {
+        let middle = [(pair[0][0] + pair[1][0]) / 2.0, (pair[0][1] + pair[1][1]) / 2.0];
+        assert!(1.0 - (middle[0] * middle[0] + middle[1] * middle[1]).sqrt() <= tolerance);
+    }
+    assert!(polyline.len() < curve.flatten(tolerance / 10.0).len());
+
+    // The derivative of a rational curve is tangent to the circle
+    let [x, y, _] = curve.evaluate(0.3);
+    let [dx, dy, _] = curve.derivative(0.3);
+    assert!((x * dx + y * dy).abs() < 1e-4);
+}
+
+#[test]
+fn curve_2d() {
+    let input = "vp 0 0\nvp 1 0\nvp 1 1\ncstype bezier\ndeg 2\ncurv2 1 2 3\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let curve = CurveEvaluator::new_2d(&obj, &obj.curves_2d[0]).unwrap();
+
+    assert!(near(curve.evaluate(0.5), [0.75, 0.25, 0.0]));
+    assert_eq!(curve.flatten(0.1).len(), 3);
+
+    // Inconsistent attributes
+    let input = "vp 0 0\nvp 1 0\nvp 1 1\ncstype bezier\ndeg 3\ncurv2 1 2 3\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    assert!(CurveEvaluator::new_2d(&obj, &obj.curves_2d[0]).is_none());
+}
+
+#[test]
+fn huge_degree() {
+    // Degrees which are too large for the control points are rejected before building a matrix
+    for degree in ["100000", "4294967295"] {
+        let input = format!("v 0 0 0\nv 1 0 0\ncstype bezier\ndeg {}\ncurv 0 1 1 2\nend\n", degree);
+        let obj = load_obj(input.as_bytes()).unwrap();
+        assert!(CurveEvaluator::new(&obj, &obj.curves[0]).is_none());
+    }
+}
@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use obj::load_mtl;
+use obj::mtl::IlluminationModel;
+use obj::mtl::{Channel, ReflectionType, TextureMap, xyz_to_linear_srgb};
+use obj::mtl::ColorSpec::{Rgb, Xyz, Spectral};
+
+fn fixture(filename: &str) -> obj::mtl::Mtl {
+    let path = Path::new("tests").join("fixtures").join(filename);
+    let mut input = BufReader::new(File::open(path).unwrap());
+
+    load_mtl(&mut input).unwrap()
+}
+
+#[test]
+fn cube() {
+    let mtl = fixture("cube.mtl");
+
+    assert_eq!(mtl.materials.len(), 1);
+
+    let material = mtl.get("Material").unwrap();
+    assert_eq!(material.name, "Material");
+    assert_eq!(material.ns, Some(96.07843));
+    assert_eq!(material.ka, Some(Rgb([0.0, 0.0, 0.0])));
+    assert_eq!(material.kd, Some(Rgb([0.64, 0.64, 0.64])));
+    assert_eq!(material.ks, Some(Rgb([0.5, 0.5, 0.5])));
+    assert_eq!(material.ni, Some(1.0));
+    assert_eq!(material.d, Some(1.0));
+    assert_eq!(material.illum, Some(IlluminationModel::HighlightOn));
+    assert_eq!(material.map_kd.as_ref().unwrap().path, "cube-uv-num.png");
+    assert_eq!(material.map_ka, None);
+}
+
+#[test]
+fn untitled() {
+    let mtl = fixture("untitled.mtl");
+
+    let names: Vec<&str> = mtl.materials.iter().map(|m| &m.name[..]).collect();
+    assert_eq!(names, ["Material", "None"]);
+
+    let none = mtl.get("None").unwrap();
+    assert_eq!(none.ns, Some(0.0));
+    assert_eq!(none.kd, Some(Rgb([0.8, 0.8, 0.8])));
+    assert_eq!(none.ks, Some(Rgb([0.8, 0.8, 0.8])));
+    assert_eq!(none.d, Some(1.0));
+    assert_eq!(none.illum.map(|illum| illum.to_u32()), Some(2));
+    assert!(mtl.get("Missing").is_none());
+}
+
+#[test]
+fn statements() {
+    let input = "newmtl glass\n\
+                 Kd 0.5\n\
+                 Tr 0.25\n\
+                 Tf 0.9 1.0 0.9\n\
+                 map_Kd textures/my diffuse.png\n\
+                 refl -type cube_top top.png\n\
+                 refl -type cube_bottom bottom.png\n\
+                 newmtl halo\n\
+                 d -halo 0.5\n";
+    let mtl = load_mtl(&mut input.as_bytes()).unwrap();
+
+    let glass = &mtl.materials[0];
+    assert_eq!(glass.kd, Some(Rgb([0.5, 0.5, 0.5])));
+    assert_eq!(glass.d, Some(0.75));
+    assert_eq!(glass.tf, Some(Rgb([0.9, 1.0, 0.9])));
+    assert_eq!(glass.map_kd.as_ref().unwrap().path, "textures/my diffuse.png");
+    assert_eq!(glass.refl.len(), 2);
+    assert_eq!(glass.refl[0].ty, Some(ReflectionType::CubeTop));
+    assert_eq!(glass.refl[1].path, "bottom.png");
+
+    let halo = &mtl.materials[1];
+    assert_eq!(halo.d, Some(0.5));
+    assert!(halo.halo);
+
+    assert!(load_mtl(&mut "Kd 1 1 1".as_bytes()).is_err());
+    assert!(load_mtl(&mut "newmtl a\nKd 1 1".as_bytes()).is_err());
+}
+
+#[test]
+fn texture_options() {
+    let input = "newmtl textured\n\
+                 map_Kd -o 0.5 0.25 -s 2 2 -blendu off -clamp on -mm 0.1 0.9 wood grain.png\n\
+                 map_bump -bm 0.3 -imfchan l -texres 512 bump.tga\n\
+                 map_Ks -t 0.1 1.5\n\
+                 decal -boost 2 -cc on decal.png\n";
+    let mtl = load_mtl(&mut input.as_bytes()).unwrap();
+    let material = &mtl.materials[0];
+
+    assert_eq!(material.map_kd, Some(TextureMap {
+        path: "wood grain.png".to_string(),
+        blendu: false,
+        clamp: true,
+        mm: [0.1, 0.9],
+        o: [0.5, 0.25, 0.0],
+        s: [2.0, 2.0, 1.0],
+        ..Default::default()
+    }));
+
+    let bump = material.map_bump.as_ref().unwrap();
+    assert_eq!(bump.path, "bump.tga");
+    assert_eq!(bump.bm, 0.3);
+    assert_eq!(bump.imfchan, Some(Channel::L));
+    assert_eq!(bump.texres, Some(512));
+    assert!(bump.blendu);
+
+    // A numeric path is never taken as a component of the preceding option
+    let specular = material.map_ks.as_ref().unwrap();
+    assert_eq!(specular.t, [0.1, 0.0, 0.0]);
+    assert_eq!(specular.path, "1.5");
+
+    let decal = material.decal.as_ref().unwrap();
+    assert_eq!(decal.boost, Some(2.0));
+    assert!(decal.cc);
+
+    assert!(load_mtl(&mut "newmtl a\nmap_Kd -bogus 1 a.png".as_bytes()).is_err());
+    assert!(load_mtl(&mut "newmtl a\nmap_Kd -clamp on".as_bytes()).is_err());
+}
+
+#[test]
+fn color_forms() {
+    let input = "newmtl colors\n\
+                 Ka spectral ident.rfl\n\
+                 Kd spectral sand.rfl 0.8\n\
+                 Ks xyz 0.5\n\
+                 Tf xyz 0.9505 1.0 1.089\n";
+    let mtl = load_mtl(&mut input.as_bytes()).unwrap();
+    let material = &mtl.materials[0];
+
+    assert_eq!(material.ka, Some(Spectral { file: "ident.rfl".to_string(), factor: 1.0 }));
+    assert_eq!(material.kd, Some(Spectral { file: "sand.rfl".to_string(), factor: 0.8 }));
+    assert_eq!(material.kd.as_ref().unwrap().to_rgb(), [0.8, 0.8, 0.8]);
+    assert_eq!(material.ks, Some(Xyz([0.5, 0.5, 0.5])));
+    assert_eq!(material.ks.as_ref().unwrap().to_rgb(), xyz_to_linear_srgb([0.5, 0.5, 0.5]));
+
+    // D65 white point is converted into white
+    let white = material.tf.as_ref().unwrap().to_rgb();
+    for &c in white.iter() {
+        assert!((c - 1.0).abs() < 1e-3);
+    }
+
+    assert!(load_mtl(&mut "newmtl a\nKd xyz 1 2".as_bytes()).is_err());
+}
+
+#[test]
+fn pbr() {
+    let input = "newmtl metal\n\
+                 Kd 0.8 0.8 0.8\n\
+                 Pr 0.35\n\
+                 Pm 1\n\
+                 Ps 0.1\n\
+                 Pc 0.5\n\
+                 Pcr 0.03\n\
+                 aniso 0.2\n\
+                 anisor 0.75\n\
+                 map_Pr metal_roughness.png\n\
+                 map_Pm metal_metallic.png\n\
+                 map_Ps metal_sheen.png\n\
+                 map_Ke metal_emissive.png\n\
+                 norm -bm 0.5 metal_normal.png\n";
+    let mtl = load_mtl(&mut input.as_bytes()).unwrap();
+    let material = &mtl.materials[0];
+
+    assert_eq!(material.pr, Some(0.35));
+    assert_eq!(material.pm, Some(1.0));
+    assert_eq!(material.ps, Some(0.1));
+    assert_eq!(material.pc, Some(0.5));
+    assert_eq!(material.pcr, Some(0.03));
+    assert_eq!(material.aniso, Some(0.2));
+    assert_eq!(material.anisor, Some(0.75));
+    assert_eq!(material.map_pr.as_ref().unwrap().path, "metal_roughness.png");
+    assert_eq!(material.map_pm.as_ref().unwrap().path, "metal_metallic.png");
+    assert_eq!(material.map_ps.as_ref().unwrap().path, "metal_sheen.png");
+    assert_eq!(material.map_ke.as_ref().unwrap().path, "metal_emissive.png");
+    assert_eq!(material.norm.as_ref().unwrap().bm, 0.5);
+}
+
+#[test]
+fn illumination_models() {
+    use obj::mtl::IlluminationModel::*;
+
+    for i in 0..12 {
+        assert_eq!(IlluminationModel::from_u32(i).to_u32(), i);
+    }
+    assert_eq!(IlluminationModel::from_u32(11), Other(11));
+
+    assert!(!ColorOnAmbientOff.has_ambient());
+    assert!(ColorOnAmbientOn.has_ambient() && !ColorOnAmbientOn.has_specular());
+    assert!(HighlightOn.has_specular() && !HighlightOn.has_reflection());
+    assert!(ReflectionRayTrace.has_reflection() && ReflectionRayTrace.has_ray_trace());
+    assert!(GlassRayTrace.has_transparency() && !GlassRayTrace.has_refraction());
+    assert!(FresnelRayTrace.has_fresnel());
+    assert!(RefractionRayTrace.has_refraction() && !RefractionRayTrace.has_fresnel());
+    assert!(RefractionFresnelRayTrace.has_refraction() && RefractionFresnelRayTrace.has_fresnel());
+    assert!(Reflection.has_reflection() && !Reflection.has_ray_trace());
+    assert!(Glass.has_transparency() && !Glass.has_ray_trace());
+    assert!(ShadowsOnInvisibleSurfaces.casts_shadows_onto_invisible_surfaces());
+    assert!(!Other(11).has_specular());
+}
@@ -1,47 +1,45 @@
-#![feature(io, path, collections)]
-
-extern crate obj;
-
-use std::old_io::{BufferedReader, File};
-use std::old_io::stdio::stderr;
-use std::simd::f32x4;
-use obj::load_obj;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use obj::{load_obj, load_obj_with_options, load_obj_with_materials, load_obj_with_resolver};
+use obj::{LoadOptions, Strictness, MemoryResolver};
+use obj::error::ParseErrorKind;
 
 fn fixture(filename: &str) -> obj::obj::Obj {
     let path = Path::new("tests").join("fixtures").join(filename);
-    let input = BufferedReader::new(File::open(&path));
+    let input = BufReader::new(File::open(path).unwrap());
 
-    load_obj(input)
+    load_obj(input).unwrap()
 }
 
 macro_rules! test {
-    ($($lhs:expr { $($x:expr, $y:expr, $z:expr, $w:expr;)* })*) => ({
+    ($($obj:ident . $field:ident { $($x:expr, $y:expr, $z:expr, $w:expr;)* })*) => ({
         $({
-            let mut index = 0us;
+            let mut index = 0usize;
             $(
-                let f32x4(x, y, z, w) = $lhs[index];
-                eq!(x, stringify!($x).parse().unwrap(), stringify!($lhs[index].x));
-                eq!(y, stringify!($y).parse().unwrap(), stringify!($lhs[index].x));
-                eq!(z, stringify!($z).parse().unwrap(), stringify!($lhs[index].x));
-                eq!(w, stringify!($w).parse().unwrap(), stringify!($lhs[index].x));
+                let [x, y, z, w] = $obj.$field[index];
+                eq!(x, stringify!($x).parse().unwrap(), stringify!($obj.$field[index].x));
+                eq!(y, stringify!($y).parse().unwrap(), stringify!($obj.$field[index].y));
+                eq!(z, stringify!($z).parse().unwrap(), stringify!($obj.$field[index].z));
+                eq!(w, stringify!($w).parse().unwrap(), stringify!($obj.$field[index].w));
                 index += 1;
             )*
-            eq!($lhs.len(), index);
+            eq!($obj.$field.len(), index);
         })*
     });
 
-    ($($lhs:expr { $($kind:ident $elem:expr)* })*) => ({
+    ($($obj:ident . $field:ident { $($kind:ident $elem:ident ! $args:tt)* })*) => ({
         $({
-            let mut index = 0us;
+            let mut index = 0usize;
             $(
-                eq!($lhs[index], obj::obj::Polygon::$kind($elem));
+                eq!($obj.$field[index], obj::obj::Polygon::$kind($elem!$args));
                 index += 1;
             )*
-            eq!($lhs.len(), index);
+            eq!($obj.$field.len(), index);
         })*
     });
 
-    ($($lhs:expr, $rhs:expr)*) => ({
+    ($($lhs:expr, $rhs:expr;)*) => ({
         $(eq!($lhs, $rhs);)*
     });
 }
@@ -54,8 +52,8 @@ macro_rules! eq {
         let right = &($rhs);
 
         if !((*left == *right) && (*right == *left)) {
-            let _ = writeln!(&mut stderr(), "\x1b[33m{}\x1b[0m should be \x1b[33m{:?}\x1b[0m, \
-                     but it was \x1b[33m{:?}\x1b[0m", $exp, *right, *left);
+            eprintln!("\x1b[33m{}\x1b[0m should be \x1b[33m{:?}\x1b[0m, \
+                       but it was \x1b[33m{:?}\x1b[0m", $exp, *right, *left);
             panic!($exp);
         }
     });
@@ -66,22 +64,22 @@ fn cube() {
     let obj = fixture("cube.obj");
 
     test! {
-        obj.name,                       "Cube".to_string()
-        obj.material_libraries,         vec![ "cube.mtl" ]
+        obj.name,                       "Cube".to_string();
+        obj.material_libraries,         vec![ "cube.mtl" ];
 
-        obj.vertices.len(),             8
-        obj.tex_coords.len(),           14
-        obj.normals.len(),              0
-        obj.param_vertices.len(),       0
+        obj.vertices.len(),             8;
+        obj.tex_coords.len(),           14;
+        obj.normals.len(),              0;
+        obj.param_vertices.len(),       0;
 
-        obj.points.len(),               0
-        obj.lines.len(),                0
-        obj.polygons.len(),             6
+        obj.points.len(),               0;
+        obj.lines.len(),                0;
+        obj.polygons.len(),             6;
 
-        obj.groups.len(),               1
-        obj.meshes.len(),               1
-        obj.smoothing_groups.len(),     0
-        obj.merging_groups.len(),       0
+        obj.groups.len(),               1;
+        obj.meshes.len(),               1;
+        obj.smoothing_groups.len(),     0;
+        obj.merging_groups.len(),       0;
     };
 
     test! {
@@ -126,17 +124,17 @@ fn cube() {
     };
 
     test! {
-        obj.groups.get("default").unwrap().points.len(),        0
-        obj.groups.get("default").unwrap().lines.len(),         0
-        obj.groups.get("default").unwrap().polygons.len(),      1
-        obj.groups.get("default").unwrap().polygons[0].start,   0
-        obj.groups.get("default").unwrap().polygons[0].end,     6
-
-        obj.meshes.get("Material").unwrap().points.len(),       0
-        obj.meshes.get("Material").unwrap().lines.len(),        0
-        obj.meshes.get("Material").unwrap().polygons.len(),     1
-        obj.meshes.get("Material").unwrap().polygons[0].start,  0
-        obj.meshes.get("Material").unwrap().polygons[0].end,    6
+        obj.groups.get("default").unwrap().points.len(),        0;
+        obj.groups.get("default").unwrap().lines.len(),         0;
+        obj.groups.get("default").unwrap().polygons.len(),      1;
+        obj.groups.get("default").unwrap().polygons[0].start,   0;
+        obj.groups.get("default").unwrap().polygons[0].end,     6;
+
+        obj.meshes.get("Material").unwrap().points.len(),       0;
+        obj.meshes.get("Material").unwrap().lines.len(),        0;
+        obj.meshes.get("Material").unwrap().polygons.len(),     1;
+        obj.meshes.get("Material").unwrap().polygons[0].start,  0;
+        obj.meshes.get("Material").unwrap().polygons[0].end,    6;
     };
 }
 
@@ -145,22 +143,22 @@ fn dome() {
     let obj = fixture("dome.obj");
 
     test! {
-        obj.name,                       "Dome".to_string()
-        obj.material_libraries,         vec![ "dome.mtl" ]
+        obj.name,                       "Dome".to_string();
+        obj.material_libraries,         vec![ "dome.mtl" ];
 
-        obj.vertices.len(),             33
-        obj.tex_coords.len(),           0
-        obj.normals.len(),              0
-        obj.param_vertices.len(),       0
+        obj.vertices.len(),             33;
+        obj.tex_coords.len(),           0;
+        obj.normals.len(),              0;
+        obj.param_vertices.len(),       0;
 
-        obj.points.len(),               0
-        obj.lines.len(),                0
-        obj.polygons.len(),             62
+        obj.points.len(),               0;
+        obj.lines.len(),                0;
+        obj.polygons.len(),             62;
 
-        obj.groups.len(),               1
-        obj.meshes.len(),               1
-        obj.smoothing_groups.len(),     2
-        obj.merging_groups.len(),       0
+        obj.groups.len(),               1;
+        obj.meshes.len(),               1;
+        obj.smoothing_groups.len(),     2;
+        obj.merging_groups.len(),       0;
     };
 
     test! {
@@ -269,28 +267,516 @@ fn dome() {
     };
 
     test! {
-        obj.groups.get("default").unwrap().points.len(),        0
-        obj.groups.get("default").unwrap().lines.len(),         0
-        obj.groups.get("default").unwrap().polygons.len(),      1
-        obj.groups.get("default").unwrap().polygons[0].start,   0
-        obj.groups.get("default").unwrap().polygons[0].end,     62
-
-        obj.meshes.get("None").unwrap().points.len(),           0
-        obj.meshes.get("None").unwrap().lines.len(),            0
-        obj.meshes.get("None").unwrap().polygons.len(),         1
-        obj.meshes.get("None").unwrap().polygons[0].start,      0
-        obj.meshes.get("None").unwrap().polygons[0].end,        62
-
-        obj.smoothing_groups[1].points.len(),                   0
-        obj.smoothing_groups[1].lines.len(),                    0
-        obj.smoothing_groups[1].polygons.len(),                 1
-        obj.smoothing_groups[1].polygons[0].start,              0
-        obj.smoothing_groups[1].polygons[0].end,                56
-
-        obj.smoothing_groups[2].points.len(),                   0
-        obj.smoothing_groups[2].lines.len(),                    0
-        obj.smoothing_groups[2].polygons.len(),                 1
-        obj.smoothing_groups[2].polygons[0].start,              56
-        obj.smoothing_groups[2].polygons[0].end,                62
+        obj.groups.get("default").unwrap().points.len(),        0;
+        obj.groups.get("default").unwrap().lines.len(),         0;
+        obj.groups.get("default").unwrap().polygons.len(),      1;
+        obj.groups.get("default").unwrap().polygons[0].start,   0;
+        obj.groups.get("default").unwrap().polygons[0].end,     62;
+
+        obj.meshes.get("None").unwrap().points.len(),           0;
+        obj.meshes.get("None").unwrap().lines.len(),            0;
+        obj.meshes.get("None").unwrap().polygons.len(),         1;
+        obj.meshes.get("None").unwrap().polygons[0].start,      0;
+        obj.meshes.get("None").unwrap().polygons[0].end,        62;
+
+        obj.smoothing_groups[&1].points.len(),                   0;
+        obj.smoothing_groups[&1].lines.len(),                    0;
+        obj.smoothing_groups[&1].polygons.len(),                 1;
+        obj.smoothing_groups[&1].polygons[0].start,              0;
+        obj.smoothing_groups[&1].polygons[0].end,                56;
+
+        obj.smoothing_groups[&2].points.len(),                   0;
+        obj.smoothing_groups[&2].lines.len(),                    0;
+        obj.smoothing_groups[&2].polygons.len(),                 1;
+        obj.smoothing_groups[&2].polygons[0].start,              56;
+        obj.smoothing_groups[&2].polygons[0].end,                62;
+    };
+}
+
+#[test]
+fn points() {
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\np 1 2\ng cloud\nusemtl dots\np -1\nv 0 0 1\np -1 -4\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.points,                                             vec![1, 2, 3, 4, 1];
+
+        obj.groups.get("default").unwrap().points.len(),        1;
+        obj.groups.get("default").unwrap().points[0].end,       2;
+        obj.groups.get("cloud").unwrap().points[0].start,       2;
+        obj.groups.get("cloud").unwrap().points[0].end,         5;
+        obj.meshes.get("dots").unwrap().points[0].start,        2;
+        obj.meshes.get("dots").unwrap().points[0].end,          5;
+    };
+}
+
+#[test]
+fn lines() {
+    use obj::obj::Line;
+
+    let input = "l 1 2 3 4\nf 1 2 3\ng guides\nl 1/1 2/2\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.lines.len(),                                        2;
+        obj.lines[0],                                           Line::P(vec![1, 2, 3, 4]);
+        obj.lines[1],                                           Line::PT(vec![(1, 1), (2, 2)]);
+
+        obj.groups.get("default").unwrap().lines[0].end,        1;
+        obj.groups.get("guides").unwrap().lines[0].start,       1;
+        obj.groups.get("guides").unwrap().lines[0].end,         2;
+        obj.groups.get("guides").unwrap().polygons.len(),       0;
+    };
+}
+
+#[test]
+fn relative_indices() {
+    use obj::obj::Line;
+
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvn 0 0 1\n\
+                 f -3 -2 -1\nf 1/-2 -2/-1 3/2\nf -3//-1 2//1 -1//-1\n\
+                 v 1 1 0\nvt 1 1\nf -4/-3/-1 -3/-2/1 -1/-1/-1\nl -1/-1 -2/-2\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.polygons {
+            P   vec![ 1, 2, 3 ]
+            PT  vec![ (1, 1), (2, 2), (3, 2) ]
+            PN  vec![ (1, 1), (2, 1), (3, 1) ]
+            PTN vec![ (1, 1, 1), (2, 2, 1), (4, 3, 1) ]
+        }
+    };
+
+    test! {
+        obj.lines,                      vec![ Line::PT(vec![ (4, 3), (3, 2) ]) ];
+    };
+}
+
+#[test]
+fn zero_based() {
+    use obj::obj::Line;
+
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\np 1\nl 1/1 2/1\nf 1//1 2//1 -1//1\n";
+    let options = LoadOptions { zero_based: true, ..Default::default() };
+    let (obj, _) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    test! {
+        obj.zero_based,                 true;
+        obj.points,                     vec![0];
+        obj.lines,                      vec![ Line::PT(vec![ (0, 0), (1, 0) ]) ];
+        obj.validate().len(),           0;
+        obj.index_base(),               0;
+        obj.position(0),                Some(0);
+    };
+
+    test! {
+        obj.polygons {
+            PN  vec![ (0, 0), (1, 0), (2, 0) ]
+        }
+    };
+
+    let obj = load_obj(input.as_bytes()).unwrap();
+    test! {
+        obj.index_base(),               1;
+        obj.position(1),                Some(0);
+        obj.position(0),                None;
+    };
+}
+
+#[test]
+fn validate() {
+    use obj::obj::{Attribute, Element, InvalidIndex};
+
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\n\nf 1/1 2/1 3/1\nf 1/1 2/2 4/1\np 5\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.validate(), vec![
+            InvalidIndex {
+                element: Element::Point, number: 0, line: 8, attribute: Attribute::Position,
+                index: 5
+            },
+            InvalidIndex {
+                element: Element::Polygon, number: 1, line: 7, attribute: Attribute::TexCoord,
+                index: 2
+            },
+            InvalidIndex {
+                element: Element::Polygon, number: 1, line: 7, attribute: Attribute::Position,
+                index: 4
+            },
+        ];
+    };
+
+    // References to `0` are rejected at their statement instead
+    match load_obj("v 0 0 0\nf 0 1 1\n".as_bytes()) {
+        Err(obj::ObjError::Parse(e)) => test! {
+            e.kind,                                         ParseErrorKind::WrongTypeOfArguments;
+            e.line,                                         2;
+            e.column,                                       3;
+        },
+        _ => panic!()
+    }
+}
+
+#[test]
+fn mixed_formats() {
+    use obj::obj::{MixedFormats, MISSING_INDEX};
+
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\nf 1/1 2 3/1/1\n";
+
+    let options = LoadOptions { mixed_formats: MixedFormats::Degrade, ..Default::default() };
+    let (degraded, _) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    let options = LoadOptions { mixed_formats: MixedFormats::Fill, ..Default::default() };
+    let (filled, _) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    let options = LoadOptions {
+        mixed_formats: MixedFormats::Fill,
+        zero_based: true,
+        ..Default::default()
+    };
+    let (rebased, _) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    test! {
+        degraded.polygons {
+            P   vec![ 1, 2, 3 ]
+        }
+
+        filled.polygons {
+            PTN vec![ (1, 1, MISSING_INDEX), (2, MISSING_INDEX, MISSING_INDEX), (3, 1, 1) ]
+        }
+
+        rebased.polygons {
+            PTN vec![ (0, 0, MISSING_INDEX), (1, MISSING_INDEX, MISSING_INDEX), (2, 0, 0) ]
+        }
+    };
+
+    assert!(filled.validate().is_empty());
+    assert!(load_obj(input.as_bytes()).is_err());
+}
+
+#[test]
+fn multiple_groups() {
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\ng arm left body\nf 1 2 3\nf 1 2 3\n\
+                 g body left\nf 1 2 3\ng\nf 1 2 3\ng arm arm\nf 1 2 3\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.groups.len(),                                       4;
+
+        obj.groups.get("default").unwrap().polygons.len(),      2;
+        obj.groups.get("default").unwrap().polygons[0].end,     1;
+        obj.groups.get("default").unwrap().polygons[1].start,   4;
+        obj.groups.get("default").unwrap().polygons[1].end,     5;
+
+        obj.groups.get("arm").unwrap().polygons.len(),          2;
+        obj.groups.get("arm").unwrap().polygons[0].start,       1;
+        obj.groups.get("arm").unwrap().polygons[0].end,         3;
+        obj.groups.get("arm").unwrap().polygons[1].start,       5;
+
+        obj.groups.get("left").unwrap().polygons.len(),         1;
+        obj.groups.get("left").unwrap().polygons[0].start,      1;
+        obj.groups.get("left").unwrap().polygons[0].end,        4;
+
+        obj.groups_of_polygon(0),                               vec!["default"];
+        obj.groups.keys().collect::<Vec<_>>(),                  vec!["default", "arm", "left", "body"];
+        obj.groups_of_polygon(1),                               vec!["arm", "left", "body"];
+        obj.groups_of_polygon(3),                               vec!["left", "body"];
+        obj.groups_of_polygon(5),                               vec!["arm"];
+        obj.groups_of_polygon(6).len(),                         0;
+    };
+}
+
+#[test]
+fn declaration_order() {
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl steel\ng zeta\nf 1 2 3\nusemtl brass\n\
+                 g alpha\nf 1 2 3\nusemtl steel\ng mu\nf 1 2 3\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.meshes.keys().collect::<Vec<_>>(),      vec!["steel", "brass"];
+        obj.groups.keys().collect::<Vec<_>>(),      vec!["zeta", "alpha", "mu"];
+        obj.meshes["steel"].polygons.len(),         2;
+    };
+}
+
+#[test]
+fn attributes() {
+    use obj::obj::{ElementAttributes, Polygon};
+
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\no Part\nusemtl steel\ns 1\ng a b\n\
+                 f 1 2 3\np 1\nmg 2\nusemtl brass\nf 2 3 1\ns off\nusemtl steel\nf 3 1 2\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let index = obj.attribute_index();
+
+    test! {
+        index.polygon(0).unwrap(), ElementAttributes {
+            material: None,
+            smoothing_group: None,
+            merging_group: None,
+            groups: vec!["default"],
+            object: None,
+        };
+        index.polygon(2).unwrap(), ElementAttributes {
+            material: Some("brass"),
+            smoothing_group: Some(1),
+            merging_group: Some(2),
+            groups: vec!["a", "b"],
+            object: Some("Part"),
+        };
+        index.polygon(3).unwrap().material,                 Some("steel");
+        index.polygon(3).unwrap().smoothing_group,          None;
+        index.point(0).unwrap().material,                   Some("steel");
+        index.point(0).unwrap().groups,                     vec!["a", "b"];
+        index.polygon(4),                                   None;
+        index.point(1),                                     None;
+        index.line(0),                                      None;
+
+        obj.polygons_in_material("steel").cloned().collect::<Vec<_>>(), vec![
+            Polygon::P(vec![1, 2, 3]),
+            Polygon::P(vec![3, 1, 2]),
+        ];
+        obj.polygons_in_group("b").count(),                 3;
+        obj.polygons_in_object("Part").count(),             3;
+        obj.polygons_in_material("missing").count(),        0;
+
+        obj.meshes.len(),                                   3;
+        obj.material_meshes().map(|(name, _)| name).collect::<Vec<_>>(), vec!["steel", "brass"];
+    };
+}
+
+#[test]
+fn objects() {
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\no Wheel\nf 1 2 3\nf 1 2 3\n\
+                 o Chassis Body\np 1 2\nl 1 2\nf 1 2 3\no Wheel\nf 1 2 3\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.name,                                           "Wheel";
+        obj.objects.keys().collect::<Vec<_>>(),             vec!["Wheel", "Chassis Body"];
+
+        obj.objects["Wheel"].polygons.len(),                2;
+        obj.objects["Wheel"].polygons[0].start,             1;
+        obj.objects["Wheel"].polygons[0].end,               3;
+        obj.objects["Wheel"].polygons[1].start,             4;
+        obj.objects["Wheel"].polygons[1].end,               5;
+        obj.objects["Wheel"].points.len(),                  0;
+
+        obj.objects["Chassis Body"].points[0].start,        0;
+        obj.objects["Chassis Body"].points[0].end,          2;
+        obj.objects["Chassis Body"].lines[0].end,           1;
+        obj.objects["Chassis Body"].polygons[0].start,      3;
+        obj.objects["Chassis Body"].polygons[0].end,        4;
+    };
+}
+
+#[test]
+fn free_form() {
+    use obj::freeform::{Basis, CurveSegment};
+    use obj::obj::Polygon;
+
+    let input = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 1\n\
+                 vp 0 0\nvp 1 0\nvp 1 1\nvp 0 1\n\
+                 cstype bezier\ndeg 3\ncurv 0 1 1 2 3 4\nparm u 0 1\nsp 1\nend\n\
+                 cstype bspline\ndeg 1\ncurv2 1 2 3 4 1\nparm u 0 0 1 2 3 4 4\nend\n\
+                 cstype rat bspline\ndeg 1 1\nsurf 0 1 0 2 1/1 2/1 3/2 -1/2\n\
+                 parm u 0 0 1 1\nparm v 0 0 2 2\ntrim 0 4 1\nhole 0 1 -1 1 2 1\n\
+                 scrv 0 4 1\nsp 2 3\nend\ncon 1 0 1 1 1 1 2 1\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    test! {
+        obj.curves.len(),                                   1;
+        obj.curves[0].attributes.basis,                     Some(Basis::Bezier);
+        obj.curves[0].attributes.degree,                    [3, 0];
+        obj.curves[0].range,                                [0.0, 1.0];
+        obj.curves[0].control_points,                       vec![1, 2, 3, 4];
+        obj.curves[0].parameters,                           vec![0.0, 1.0];
+        obj.curves[0].special_points,                       vec![1];
+
+        obj.curves_2d.len(),                                1;
+        obj.curves_2d[0].attributes.basis,                  Some(Basis::BSpline);
+        obj.curves_2d[0].control_points,                    vec![1, 2, 3, 4, 1];
+        obj.curves_2d[0].parameters.len(),                  7;
+
+        obj.surfaces.len(),                                 1;
+        obj.surfaces[0].attributes.rational,                true;
+        obj.surfaces[0].attributes.degree,                  [1, 1];
+        obj.surfaces[0].range,                              [[0.0, 1.0], [0.0, 2.0]];
+        obj.surfaces[0].control_points, Polygon::PT(vec![(1, 1), (2, 1), (3, 2), (4, 2)]);
+        obj.surfaces[0].parameters[1],                      vec![0.0, 0.0, 2.0, 2.0];
+        obj.surfaces[0].trims, vec![vec![CurveSegment { range: [0.0, 4.0], curve: 1 }]];
+        obj.surfaces[0].holes[0].len(),                     2;
+        obj.surfaces[0].holes[0][1].range,                  [1.0, 2.0];
+        obj.surfaces[0].special_curves.len(),               1;
+        obj.surfaces[0].special_points,                     vec![2, 3];
+
+        obj.connections.len(),                              1;
+        obj.connections[0].surfaces,                        [1, 1];
+        obj.connections[0].curves[1], CurveSegment { range: [1.0, 2.0], curve: 1 };
+    };
+
+    let options = LoadOptions { zero_based: true, ..Default::default() };
+    let (obj, _) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    test! {
+        obj.curves[0].control_points,                       vec![0, 1, 2, 3];
+        obj.surfaces[0].control_points, Polygon::PT(vec![(0, 0), (1, 0), (2, 1), (3, 1)]);
+        obj.surfaces[0].holes[0][0].curve,                  0;
+        obj.connections[0].surfaces,                        [0, 0];
+        obj.connections[0].curves[0].curve,                 0;
+    };
+}
+
+#[test]
+fn malformed() {
+    macro_rules! fail {
+        ($($input:expr => $kind:ident)*) => ({
+            $(match load_obj($input.as_bytes()) {
+                Err(obj::ObjError::Parse(e)) => eq!(e.kind, ParseErrorKind::$kind, $input),
+                _ => panic!($input)
+            })*
+        })
+    }
+
+    fail! {
+        "v 1.0 x 3.0"           => WrongTypeOfArguments
+        "v 1.0 2.0"             => WrongNumberOfArguments
+        "f 1 2"                 => WrongNumberOfArguments
+        "f 1/1 2 3/3"           => InconsistentVertexFormat
+        "f 1//a 2//2 3//3"      => WrongTypeOfArguments
+        "unknown 1 2 3"         => UnexpectedStatement
+        "p"                     => WrongNumberOfArguments
+        "l 1"                   => WrongNumberOfArguments
+        "l 1/1 2"               => InconsistentVertexFormat
+        "l 1//1 2//2"           => WrongTypeOfArguments
+        "v 0 0 0\nf 1 -1 -2"    => WrongTypeOfArguments
+        "cstype nurbs"          => WrongTypeOfArguments
+        "deg 1 2 3"             => WrongNumberOfArguments
+        "parm u 0 1"            => UnexpectedStatement
+        "end"                   => UnexpectedStatement
+        "v 0 0 0\np 0"          => WrongTypeOfArguments
+        "v 0 0 0\np -2"         => WrongTypeOfArguments
+        "con 1 0 1 1"           => WrongNumberOfArguments
+        "con 0 0 1 1 1 0 1 1"   => WrongTypeOfArguments
+    };
+}
+
+#[test]
+fn lenient() {
+    let input = "v 0 0 0\nv 1 0 0\nvendor_ext 1 2\nv 0 1 x\nv 0 1 0\nf 1 2 3\n";
+    let options = LoadOptions { strictness: Strictness::Lenient, ..Default::default() };
+    let (obj, warnings) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    test! {
+        obj.vertices.len(),             3;
+        obj.polygons.len(),             1;
+
+        warnings.len(),                 2;
+        warnings[0].kind,               ParseErrorKind::UnexpectedStatement;
+        warnings[0].line,               3;
+        warnings[0].text,               "vendor_ext 1 2";
+        warnings[1].kind,               ParseErrorKind::WrongTypeOfArguments;
+        warnings[1].line,               4;
+        warnings[1].column,             7;
+    };
+
+    assert!(load_obj(input.as_bytes()).is_err());
+
+    // A skipped statement leaves no partial elements
+    let input = "v 0 0 0\nv 1 0 0\np 1 2 x\np 2\n";
+    let (obj, warnings) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    test! {
+        obj.points,                     vec![2];
+        obj.source_lines.points,        vec![4];
+        warnings.len(),                 1;
+    };
+
+    // Body statements of a skipped free-form element are not applied to the previous one
+    let input = "v 0 0 0\nv 1 0 0\ncstype bspline\ndeg 1\ncurv 0 1 1 2\nparm u 0 0 1 1\n\
+                 surf 0 1 0 1\nparm u 5 5 5\nend\n";
+    let (obj, warnings) = load_obj_with_options(input.as_bytes(), &options).unwrap();
+
+    test! {
+        obj.curves[0].parameters,       vec![0.0, 0.0, 1.0, 1.0];
+        obj.surfaces.len(),             0;
+        warnings.len(),                 3;
+        warnings[1].kind,               ParseErrorKind::UnexpectedStatement;
+        warnings[1].line,               8;
+    };
+}
+
+#[test]
+fn materials() {
+    let path = Path::new("tests").join("fixtures").join("cube.obj");
+    let loaded = load_obj_with_materials(&path, &LoadOptions::default()).unwrap();
+
+    test! {
+        loaded.warnings.len(),                          0;
+        loaded.materials.materials.len(),               1;
+        loaded.material("Material").unwrap().name,      "Material";
+    };
+
+    let path = Path::new("tests").join("fixtures").join("dome.obj");
+    let loaded = load_obj_with_materials(&path, &LoadOptions::default()).unwrap();
+
+    test! {
+        loaded.obj.polygons.len(),                      62;
+        loaded.materials.materials.len(),               0;
+        loaded.warnings.len(),                          2;
+        loaded.warnings[0].1.kind,                      ParseErrorKind::MissingMaterialLibrary;
+        loaded.warnings[0].1.token,                     Some("dome.mtl".to_string());
+        loaded.warnings[1].1.kind,                      ParseErrorKind::UndefinedMaterial;
+        loaded.warnings[1].1.token,                     Some("None".to_string());
+    };
+    assert!(loaded.material("None").is_none());
+}
+
+#[test]
+fn resolver() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("scene/main.obj", b"mtllib ../lib/steel.mtl missing.mtl\n\
+                                        call parts/bolt.obj\n\
+                                        call parts/nut.obj\n\
+                                        usemtl steel\n".to_vec());
+    resolver.insert("lib/steel.mtl", b"newmtl steel\nmap_Kd tex/steel.png\n".to_vec());
+    resolver.insert("scene/parts/bolt.obj", b"call ../main.obj\nv 0 0 0\n".to_vec());
+
+    let loaded = load_obj_with_resolver(&resolver, "scene/main.obj", &LoadOptions::default());
+    let loaded = loaded.unwrap();
+
+    test! {
+        loaded.path,                                    "scene/main.obj";
+        loaded.material("steel").unwrap().map_kd.as_ref().unwrap().path, "lib/tex/steel.png";
+
+        loaded.warnings.len(),                          2;
+        loaded.warnings[0].0,                           "scene/main.obj";
+        loaded.warnings[0].1.kind,                      ParseErrorKind::MissingMaterialLibrary;
+        loaded.warnings[0].1.token,                     Some("missing.mtl".to_string());
+        loaded.warnings[1].0,                           "scene/main.obj";
+        loaded.warnings[1].1.kind,                      ParseErrorKind::MissingCallTarget;
+        loaded.warnings[1].1.token,                     Some("parts/nut.obj".to_string());
+
+        loaded.calls.len(),                             1;
+        loaded.calls[0].path,                           "scene/parts/bolt.obj";
+        loaded.calls[0].obj.vertices.len(),             1;
+        loaded.calls[0].warnings.len(),                 1;
+        loaded.calls[0].warnings[0].0,                  "scene/parts/bolt.obj";
+        loaded.calls[0].warnings[0].1.kind,             ParseErrorKind::RecursiveCall;
+    };
+
+    // Warnings of material libraries are paired with the path of the library
+    resolver.insert("lib/steel.mtl", b"newmtl steel\nNs x\n".to_vec());
+    resolver.insert("scene/main.obj", b"mtllib ../lib/steel.mtl\nv 0 0 x\n".to_vec());
+
+    let options = LoadOptions { strictness: Strictness::Lenient, ..Default::default() };
+    let loaded = load_obj_with_resolver(&resolver, "scene/main.obj", &options).unwrap();
+
+    test! {
+        loaded.warnings.len(),                          2;
+        loaded.warnings[0].0,                           "scene/main.obj";
+        loaded.warnings[0].1.line,                      2;
+        loaded.warnings[1].0,                           "lib/steel.mtl";
+        loaded.warnings[1].1.line,                      2;
     };
 }
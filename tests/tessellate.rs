@@ -0,0 +1,170 @@
+use obj::{load_obj, load_obj_with_options, tessellate, LoadOptions, TessellateOptions};
+use obj::obj::Polygon;
+
+const PLANE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\n\
+                     cstype bspline\ndeg 1 1\nsurf 0 1 0 1 1 2 3 4\n\
+                     parm u 0 0 1 1\nparm v 0 0 1 1\n";
+
+#[test]
+fn plane() {
+    let obj = load_obj(format!("{}end\n", PLANE).as_bytes()).unwrap();
+    let options = TessellateOptions { resolution: 2, ..Default::default() };
+    let tessellation = tessellate(&obj, &options);
+
+    assert_eq!(tessellation.vertices.len(), 9);
+    assert_eq!(tessellation.polygons.len(), 8);
+    assert_eq!(tessellation.surfaces[0].end, 8);
+    assert_eq!(tessellation.polygons[0], Polygon::P(vec![1, 2, 3]));
+    assert_eq!(tessellation.polygons[1], Polygon::P(vec![1, 3, 4]));
+    assert_eq!(tessellation.vertices[2], [0.5, 0.5, 0.0, 1.0]);
+
+    let (obj, _) = load_obj_with_options(format!("{}end\n", PLANE).as_bytes(),
+                                         &LoadOptions { zero_based: true, ..Default::default() })
+        .unwrap();
+    let tessellation = tessellate(&obj, &options);
+    assert_eq!(tessellation.polygons[0], Polygon::P(vec![0, 1, 2]));
+}
+
+#[test]
+fn hole() {
+    let input = format!("vp 0.25 0.25\nvp 0.75 0.25\nvp 0.75 0.75\nvp 0.25 0.75\n\
+                         cstype bspline\ndeg 1\ncurv2 1 2 3 4 1\nparm u 0 0 1 2 3 4 4\nend\n\
+                         {}hole 0 4 1\nend\n", PLANE);
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let tessellation = tessellate(&obj, &TessellateOptions::default());
+
+    // The 2x2 cells in the middle of the 4x4 grid are removed
+    assert_eq!(tessellation.polygons.len(), 24);
+    assert_eq!(tessellation.vertices.len(), 24);
+}
+
+#[test]
+fn trim() {
+    // A triangle which crosses the cells of the grid diagonally, with a square hole
+    let input = format!("vp 0.1 0.1\nvp 0.9 0.2\nvp 0.3 0.8\n\
+                         vp 0.35 0.3\nvp 0.45 0.3\nvp 0.45 0.4\nvp 0.35 0.4\n\
+                         cstype bspline\ndeg 1\ncurv2 1 2 3 1\nparm u 0 0 1 2 3 3\nend\n\
+                         curv2 4 5 6 7 4\nparm u 0 0 1 2 3 4 4\nend\n\
+                         {}trim 0 3 1\nhole 0 4 2\nend\n", PLANE);
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let tessellation = tessellate(&obj, &TessellateOptions::default());
+
+    // The plane maps the parameter space onto the xy plane, so the triangles cover the area
+    // between the loops exactly
+    let mut area = 0.0;
+    for polygon in tessellation.polygons.iter() {
+        let vertices = match *polygon {
+            Polygon::P(ref vertices) => vertices,
+            _ => panic!()
+        };
+        let [a, b, c] = [0, 1, 2].map(|k| tessellation.vertices[vertices[k] as usize - 1]);
+        let cross = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        assert!(cross > 0.0);
+        area += cross / 2.0;
+    }
+    assert!((area - (0.27 - 0.01)).abs() < 1e-5);
+}
+
+#[test]
+fn rational() {
+    // A quarter of the unit cylinder
+    let w = 0.5f32.sqrt();
+    let input = format!("v 1 0 0\nv 1 1 0 {w}\nv 0 1 0\nv 1 0 1\nv 1 1 1 {w}\nv 0 1 1\n\
+                         cstype rat bspline\ndeg 2 1\nsurf 0 1 0 1 1 2 3 4 5 6\n\
+                         parm u 0 0 0 1 1 1\nparm v 0 0 1 1\nend\n", w = w);
+    let obj = load_obj(input.as_bytes()).unwrap();
+
+    let options = TessellateOptions { tolerance: Some(0.001), ..Default::default() };
+    let tessellation = tessellate(&obj, &options);
+
+    let radius = |p: [f32; 4]| (p[0] * p[0] + p[1] * p[1]).sqrt();
+    for vertex in tessellation.vertices.iter() {
+        assert!((radius(*vertex) - 1.0).abs() < 1e-5);
+    }
+
+    // Edges around the cylinder must be within the tolerance from the arc
+    for polygon in tessellation.polygons.iter() {
+        let vertices = match *polygon {
+            Polygon::P(ref vertices) => vertices,
+            _ => panic!()
+        };
+        for k in 0..3 {
+            let a = tessellation.vertices[vertices[k] as usize - 1];
+            let b = tessellation.vertices[vertices[(k + 1) % 3] as usize - 1];
+            if a[2] != b[2] { continue }
+
+            let middle = [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0, a[2], 1.0];
+            assert!(1.0 - radius(middle) <= 0.001);
+        }
+    }
+
+    // Without tolerance, the single knot span in each direction is divided into 4 segments
+    let coarse = tessellate(&obj, &TessellateOptions::default());
+    assert_eq!(coarse.polygons.len(), 32);
+    assert!(tessellation.polygons.len() > coarse.polygons.len());
+}
+
+#[test]
+fn unsupported() {
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\ncstype cardinal\ndeg 3 3\n\
+                 surf 0 1 0 1 1 2 3 4\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let tessellation = tessellate(&obj, &TessellateOptions::default());
+
+    assert_eq!(tessellation.surfaces.len(), 1);
+    assert_eq!(tessellation.surfaces[0].start, tessellation.surfaces[0].end);
+    assert!(tessellation.polygons.is_empty());
+}
+
+#[test]
+fn huge_degree() {
+    // The degree doesn't match the control points, so no knot vector is built for it
+    let input = "v 0 0 0\ncstype bezier\ndeg 4294967295 1\nsurf 0 1 0 1 1\nparm u 0 1 2\nend\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let tessellation = tessellate(&obj, &TessellateOptions::default());
+
+    assert_eq!(tessellation.surfaces.len(), 1);
+    assert!(tessellation.polygons.is_empty());
+}
+
+#[test]
+fn stitch() {
+    // Two squares which meet at x = 1, sampled at different resolutions
+    let input = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nv 2 0 0\nv 2 1 0\n\
+                 vp 1 0\nvp 1 1\nvp 0 0\nvp 0 1\n\
+                 cstype bspline\ndeg 1\ncurv2 1 2\nparm u 0 0 1 1\nend\n\
+                 curv2 3 4\nparm u 0 0 1 1\nend\n\
+                 deg 1 1\nstech cparma 2 2\nsurf 0 1 0 1 1 2 3 4\n\
+                 parm u 0 0 1 1\nparm v 0 0 1 1\nend\n\
+                 stech cparma 3 3\nsurf 0 1 0 1 2 5 4 6\n\
+                 parm u 0 0 1 1\nparm v 0 0 1 1\nend\n\
+                 con 1 0 1 1 2 0 1 2\n";
+    let obj = load_obj(input.as_bytes()).unwrap();
+    let on_edge = |tessellation: &obj::Tessellation| {
+        tessellation.vertices.iter().filter(|vertex| (vertex[0] - 1.0).abs() < 1e-6).count()
+    };
+
+    let tessellation = tessellate(&obj, &TessellateOptions::default());
+    assert_eq!(tessellation.vertices.len(), 25);
+    assert_eq!(on_edge(&tessellation), 7);
+
+    // Both surfaces are sampled at v = 0, 1/3, 1/2, 2/3 and 1, and share the vertices there
+    let options = TessellateOptions { stitch: true, ..Default::default() };
+    let tessellation = tessellate(&obj, &options);
+    assert_eq!(tessellation.vertices.len(), 30);
+    assert_eq!(on_edge(&tessellation), 5);
+
+    // Every vertex on the edge is used by both surfaces
+    let uses = |surface: usize, vertex: u32| {
+        let range = &tessellation.surfaces[surface];
+        tessellation.polygons[range.start..range.end].iter().any(|polygon| match *polygon {
+            Polygon::P(ref vertices) => vertices.contains(&vertex),
+            _ => false
+        })
+    };
+    for (i, vertex) in tessellation.vertices.iter().enumerate() {
+        if vertex[0] == 1.0 {
+            assert!(uses(0, i as u32 + 1) && uses(1, i as u32 + 1));
+        }
+    }
+}